@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::graphql_proxy::GraphQLProxy;
+use crate::models::{Account, TransferRequest};
+use crate::service_logs::strip_ansi;
+use crate::wallet::validate_chain_id;
+
+/// Decimal places of a linera `Amount`
+const AMOUNT_DECIMALS: usize = 18;
+
+/// Owner that stands for the chain's own balance in the service's GraphQL API
+const CHAIN_OWNER: &str = "0x00";
+
+/// The service's `transfer` mutation, which returns the certificate hash
+const TRANSFER_MUTATION: &str = "mutation Transfer($chainId: ChainId!, $owner: AccountOwner!, \
+    $recipient: Account!, $amount: Amount!) { \
+    transfer(chainId: $chainId, owner: $owner, recipient: $recipient, amount: $amount) }";
+
+/// Reject accounts before they reach the CLI
+pub fn validate_account(account: &Account) -> Result<(), ApiError> {
+    validate_chain_id(&account.chain_id)?;
+    match &account.owner {
+        Some(owner) => validate_owner(owner),
+        None => Ok(()),
+    }
+}
+
+/// Owners are 32-byte linera or 20-byte EVM addresses
+pub fn validate_owner(owner: &str) -> Result<(), ApiError> {
+    let valid = owner.strip_prefix("0x").is_some_and(|hex| {
+        matches!(hex.len(), 40 | 64) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidOwner(owner.to_string()))
+    }
+}
+
+/// Amounts are passed to the CLI as given, so only plain positive decimals are accepted
+pub fn validate_amount(amount: &str) -> Result<(), ApiError> {
+    let invalid = || ApiError::InvalidAmount(amount.to_string());
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() {
+        return Err(invalid());
+    }
+    if !(whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit()))
+        || fraction.len() > AMOUNT_DECIMALS
+    {
+        return Err(invalid());
+    }
+    if whole.chars().chain(fraction.chars()).all(|c| c == '0') {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// Balance printed by `linera query-balance`, e.g. `10.` or `0.5`, without the trailing dot.
+///
+/// The amount is the last line of output; anything before it is logging.
+pub fn parse_balance(output: &str) -> Result<String> {
+    let line = output
+        .lines()
+        .rev()
+        .map(strip_ansi)
+        .map(|line| line.trim().to_string())
+        .find(|line| !line.is_empty())
+        .ok_or_else(|| anyhow!("query-balance printed nothing"))?;
+
+    let is_amount =
+        line.chars().all(|c| c.is_ascii_digit() || c == '.') && line.matches('.').count() <= 1;
+    if !is_amount {
+        return Err(anyhow!("Unexpected query-balance output: {}", line));
+    }
+    Ok(line.trim_end_matches('.').to_string())
+}
+
+/// Transfer through the running service, which answers with the certificate hash
+pub async fn transfer(proxy: &GraphQLProxy, req: &TransferRequest) -> Result<String> {
+    let account = |account: &Account| {
+        json!({
+            "chainId": account.chain_id,
+            "owner": account.owner.as_deref().unwrap_or(CHAIN_OWNER),
+        })
+    };
+    let variables = json!({
+        "chainId": req.from.chain_id,
+        "owner": req.from.owner.as_deref().unwrap_or(CHAIN_OWNER),
+        "recipient": account(&req.to),
+        "amount": req.amount,
+    });
+    let response = proxy
+        .system_query(TRANSFER_MUTATION, Some(variables))
+        .await?;
+
+    if let Some(error) = response["errors"]
+        .as_array()
+        .and_then(|errors| errors.first())
+    {
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        return Err(ApiError::GraphQL(message.to_string()).into());
+    }
+    response
+        .pointer("/data/transfer")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            ApiError::GraphQL("Transfer returned no certificate hash".to_string()).into()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN_ID: &str = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4";
+    const OWNER: &str = "0x3a6ba9e1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5";
+
+    #[test]
+    fn amounts_must_be_positive_decimals() {
+        for amount in ["1", "1.", "0.5", "100.000000000000000001"] {
+            assert!(validate_amount(amount).is_ok(), "{}", amount);
+        }
+        for amount in [
+            "",
+            ".",
+            ".5",
+            "0",
+            "0.000",
+            "-1",
+            "1e3",
+            "1,5",
+            "1.2.3",
+            "0.0000000000000000001",
+        ] {
+            assert!(validate_amount(amount).is_err(), "{}", amount);
+        }
+    }
+
+    #[test]
+    fn owners_are_hex_addresses() {
+        assert!(validate_owner(OWNER).is_ok());
+        assert!(validate_owner("0x71C7656EC7ab88b098defB751B7401B5f6d8976F").is_ok());
+        assert!(validate_owner(&OWNER[2..]).is_err());
+        assert!(validate_owner("0xabc@evil").is_err());
+    }
+
+    #[test]
+    fn accounts_use_cli_syntax() {
+        let account: Account = format!("{}@{}", OWNER, CHAIN_ID).parse().unwrap();
+        assert_eq!(account.owner.as_deref(), Some(OWNER));
+        assert_eq!(account.to_string(), format!("{}@{}", OWNER, CHAIN_ID));
+        assert!(validate_account(&account).is_ok());
+
+        let chain: Account = CHAIN_ID.parse().unwrap();
+        assert_eq!(chain.owner, None);
+        assert_eq!(chain.to_string(), CHAIN_ID);
+    }
+
+    #[test]
+    fn balance_is_last_line_without_trailing_dot() {
+        let output = "2025-09-02T08:14:03Z  INFO linera: Reading wallet\n10.\n";
+        assert_eq!(parse_balance(output).unwrap(), "10");
+        assert_eq!(parse_balance("0.25\n").unwrap(), "0.25");
+        assert!(parse_balance("Error: chain not found\n").is_err());
+        assert!(parse_balance("").is_err());
+    }
+}
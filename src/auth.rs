@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{Request, State},
+    http::{header, Method},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::error::ApiError;
+use crate::AppState;
+
+/// Query parameter accepted instead of the `Authorization` header on GET requests,
+/// since browsers cannot set headers on EventSource and WebSocket connections
+const TOKEN_QUERY_PARAM: &str = "access_token";
+
+/// What a token is allowed to do
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// GET endpoints only: status, logs, wallet info, instance listing
+    Read,
+    /// Everything
+    Admin,
+}
+
+/// An API token as stored in the tokens file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub name: String,
+    pub token: String,
+    pub scope: Scope,
+}
+
+/// Bearer tokens accepted by the API
+pub struct Auth {
+    tokens: Vec<ApiToken>,
+}
+
+impl Auth {
+    pub fn new(tokens: Vec<ApiToken>) -> Self {
+        Self { tokens }
+    }
+
+    /// Tokens from `LINERA_API_TOKEN` and the tokens `file`.
+    ///
+    /// On first run, when neither exists, an admin token is generated and saved
+    /// so the server is never reachable without one.
+    pub fn load(file: &Path) -> Self {
+        let mut tokens = Vec::new();
+        if let Ok(token) = std::env::var("LINERA_API_TOKEN") {
+            tokens.push(ApiToken {
+                name: "LINERA_API_TOKEN".to_string(),
+                token,
+                scope: Scope::Admin,
+            });
+        }
+
+        match std::fs::read(file) {
+            Ok(json) => match serde_json::from_slice::<Vec<ApiToken>>(&json) {
+                Ok(saved) => tokens.extend(saved),
+                Err(e) => warn!("Ignoring unreadable tokens file {}: {}", file.display(), e),
+            },
+            Err(_) if tokens.is_empty() => match generate_admin_token(file) {
+                Ok(token) => {
+                    // Logs are often shipped elsewhere; only show the token to someone at the terminal
+                    warn!(
+                        "Generated an admin API token and saved it to {}",
+                        file.display()
+                    );
+                    if std::io::stdout().is_terminal() {
+                        println!("Admin API token: {}", token.token);
+                    }
+                    tokens.push(token);
+                }
+                Err(e) => warn!("Failed to generate an API token: {}", e),
+            },
+            Err(_) => {}
+        }
+
+        if tokens.is_empty() {
+            warn!("No API tokens configured; every API request will be rejected");
+        } else {
+            info!("Loaded {} API token(s)", tokens.len());
+        }
+        Self::new(tokens)
+    }
+
+    /// Check `presented` grants at least `needed`
+    pub fn authorize(&self, presented: Option<&str>, needed: Scope) -> Result<(), ApiError> {
+        let presented = presented.ok_or(ApiError::Unauthorized)?;
+        let token = self
+            .tokens
+            .iter()
+            .find(|t| constant_time_eq(t.token.as_bytes(), presented.as_bytes()))
+            .ok_or(ApiError::Unauthorized)?;
+
+        if token.scope >= needed {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(token.name.clone()))
+        }
+    }
+}
+
+/// Middleware rejecting requests without a token of sufficient scope.
+///
+/// Reading (GET) needs a read token; anything that changes state needs an admin token.
+pub async fn require_token(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let reading = matches!(*request.method(), Method::GET | Method::HEAD);
+    let needed = if reading { Scope::Read } else { Scope::Admin };
+
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    let query = reading
+        .then(|| query_token(request.uri().query().unwrap_or("")))
+        .flatten();
+
+    state.auth.authorize(bearer.or(query.as_deref()), needed)?;
+    Ok(next.run(request).await)
+}
+
+fn query_token(query: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == TOKEN_QUERY_PARAM)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Create the tokens file with a single random admin token, readable only by us
+fn generate_admin_token(file: &Path) -> Result<ApiToken> {
+    let token = ApiToken {
+        name: "admin".to_string(),
+        token: random_token()?,
+        scope: Scope::Admin,
+    };
+
+    write_private(file, &serde_json::to_vec_pretty(&[&token])?, true)?;
+    Ok(token)
+}
+
+/// Add `token` to the tokens file, creating it if needed
+pub fn save_token(file: &Path, token: &ApiToken) -> Result<()> {
+    let mut tokens: Vec<ApiToken> = match std::fs::read(file) {
+        Ok(json) => serde_json::from_slice(&json)
+            .map_err(|e| anyhow!("Unreadable tokens file {}: {}", file.display(), e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(anyhow!("Failed to read {}: {}", file.display(), e)),
+    };
+
+    if tokens.iter().any(|t| t.name == token.name) {
+        return Err(anyhow!(
+            "{} already has a token named {:?}",
+            file.display(),
+            token.name
+        ));
+    }
+    tokens.push(token.clone());
+
+    write_private(file, &serde_json::to_vec_pretty(&tokens)?, false)
+}
+
+/// Write `json` to `file`, which is created readable only by us
+fn write_private(file: &Path, json: &[u8], create_new: bool) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true);
+    if create_new {
+        options.create_new(true);
+    } else {
+        options.create(true).truncate(true);
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    std::io::Write::write_all(&mut options.open(file)?, json)
+        .map_err(|e| anyhow!("Failed to write {}: {}", file.display(), e))
+}
+
+/// 32 random bytes, hex encoded
+pub fn random_token() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Compare without leaking how many leading bytes match
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_run_token_is_private_and_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tokens.json");
+
+        let token = generate_admin_token(&file).unwrap();
+        assert_eq!(token.token.len(), 64);
+        assert_eq!(token.scope, Scope::Admin);
+
+        let saved: Vec<ApiToken> = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+        assert_eq!(saved[0].token, token.token);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert!(generate_admin_token(&file).is_err());
+    }
+
+    #[test]
+    fn admin_scope_includes_read() {
+        let auth = Auth::new(vec![ApiToken {
+            name: "admin".to_string(),
+            token: "secret".to_string(),
+            scope: Scope::Admin,
+        }]);
+
+        assert!(auth.authorize(Some("secret"), Scope::Read).is_ok());
+        assert!(auth.authorize(Some("secret"), Scope::Admin).is_ok());
+        assert!(auth.authorize(Some("secre"), Scope::Read).is_err());
+        assert!(auth.authorize(None, Scope::Read).is_err());
+    }
+
+    #[test]
+    fn saved_tokens_are_appended_under_unique_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tokens.json");
+        let token = |name: &str, scope| ApiToken {
+            name: name.to_string(),
+            token: random_token().unwrap(),
+            scope,
+        };
+
+        save_token(&file, &token("admin", Scope::Admin)).unwrap();
+        save_token(&file, &token("dashboard", Scope::Read)).unwrap();
+        assert!(save_token(&file, &token("dashboard", Scope::Admin)).is_err());
+
+        let saved: Vec<ApiToken> = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+        let names: Vec<_> = saved.iter().map(|t| (t.name.as_str(), t.scope)).collect();
+        assert_eq!(names, [("admin", Scope::Admin), ("dashboard", Scope::Read)]);
+    }
+}
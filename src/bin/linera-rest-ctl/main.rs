@@ -0,0 +1,593 @@
+//! Command line client for a remote linera-rest-server
+
+mod profile;
+mod render;
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use linera_rest_server::client::Client;
+use linera_rest_server::models::{
+    self, Account, AddOwnerRequest, CreateInstanceRequest, GraphQLProxyRequest, LogStream,
+    LogsQuery, OpenChainRequest, OpenMultiOwnerChainRequest, RemoveOwnerRequest,
+    RequestChainRequest, StartServiceRequest, StopServiceRequest, TransferRequest,
+};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use crate::profile::{Profile, Profiles, DEFAULT_URL};
+
+/// Drive a linera-rest-server from another machine
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Saved profile to use [default: `default_profile` of the profiles file]
+    #[arg(long, short, global = true, env = "LINERA_REST_PROFILE")]
+    profile: Option<String>,
+    /// Server URL, overriding the profile
+    #[arg(long, global = true, env = "LINERA_REST_URL")]
+    url: Option<String>,
+    /// API token, overriding the profile
+    #[arg(long, global = true, env = "LINERA_REST_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// Act on a named instance instead of the default one
+    #[arg(long, short, global = true)]
+    instance: Option<String>,
+    /// Print the response's `data` as JSON instead of a summary
+    #[arg(long, global = true)]
+    json: bool,
+    /// Profiles file
+    #[arg(long, global = true, env = "LINERA_REST_CTL_CONFIG")]
+    profiles_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the linera service
+    #[command(subcommand)]
+    Service(ServiceCommand),
+    /// Manage the wallet
+    #[command(subcommand)]
+    Wallet(WalletCommand),
+    /// Manage chain owners
+    #[command(subcommand)]
+    Owner(OwnerCommand),
+    /// Open, close and list chains
+    #[command(subcommand)]
+    Chain(ChainCommand),
+    /// Manage named instances, each with its own wallet and service
+    #[command(subcommand)]
+    Instance(InstanceCommand),
+    /// Show the balance of a chain and of owners on it
+    Balance {
+        chain_id: String,
+        /// Owner to report (repeatable) [default: the wallet's owner of the chain]
+        #[arg(long = "owner")]
+        owners: Vec<String>,
+    },
+    /// Transfer tokens; accounts are `chain_id` or `owner@chain_id`
+    Transfer {
+        amount: String,
+        #[arg(long)]
+        from: Account,
+        #[arg(long)]
+        to: Account,
+    },
+    /// Send a GraphQL query; without --chain-id it goes to the system API
+    Graphql {
+        query: String,
+        #[arg(long)]
+        chain_id: Option<String>,
+        #[arg(long, requires = "chain_id")]
+        app_id: Option<String>,
+        /// Variables as a JSON object
+        #[arg(long)]
+        variables: Option<String>,
+    },
+    /// Check the server is up
+    Health,
+    /// Manage saved server profiles
+    #[command(subcommand)]
+    Profile(ProfileCommand),
+}
+
+#[derive(Subcommand)]
+enum ServiceCommand {
+    /// Start linera service
+    Start {
+        /// Service port [default: the server's configured port]
+        #[arg(long)]
+        port: Option<u16>,
+        /// `--storage` value, e.g. `rocksdb:/path/to/db`
+        #[arg(long)]
+        storage: Option<String>,
+        /// Seconds to wait for the GraphQL endpoint
+        #[arg(long)]
+        startup_timeout: Option<u64>,
+        /// Restart the service when it exits on its own
+        #[arg(long, value_enum)]
+        restart: Option<RestartMode>,
+        /// Give up after this many consecutive restarts
+        #[arg(long, requires = "restart")]
+        max_restarts: Option<u32>,
+    },
+    /// Stop linera service
+    Stop {
+        /// Seconds to wait after SIGTERM before sending SIGKILL
+        #[arg(long)]
+        grace_period: Option<u64>,
+    },
+    /// Show the service state
+    Status,
+    /// Show recent service output
+    Logs {
+        #[arg(long, default_value_t = 100)]
+        lines: usize,
+        #[arg(long, value_enum)]
+        stream: Option<Stream>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RestartMode {
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl From<RestartMode> for models::RestartMode {
+    fn from(mode: RestartMode) -> Self {
+        match mode {
+            RestartMode::Never => models::RestartMode::Never,
+            RestartMode::OnFailure => models::RestartMode::OnFailure,
+            RestartMode::Always => models::RestartMode::Always,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl From<Stream> for LogStream {
+    fn from(stream: Stream) -> Self {
+        match stream {
+            Stream::Stdout => LogStream::Stdout,
+            Stream::Stderr => LogStream::Stderr,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum WalletCommand {
+    /// Create a wallet with a chain from the faucet
+    Init {
+        #[arg(long)]
+        faucet: String,
+    },
+    /// Add another chain from the faucet to the wallet
+    RequestChain {
+        /// Defaults to the testnet faucet
+        #[arg(long)]
+        faucet: Option<String>,
+        /// Make the new chain the default
+        #[arg(long)]
+        set_default: bool,
+    },
+    /// Show the wallet's chains
+    Info,
+    /// Make a chain of the wallet its default
+    SetDefault { chain_id: String },
+    /// Remove a chain from the wallet
+    Forget { chain_id: String },
+    /// Generate a new keypair
+    Keygen,
+}
+
+#[derive(Subcommand)]
+enum OwnerCommand {
+    /// Add owners to a chain
+    Add {
+        #[arg(long)]
+        chain_id: String,
+        /// Owner public key (repeatable)
+        #[arg(long = "public-key", required = true)]
+        public_keys: Vec<String>,
+        /// Weight of the new owners [default: 100]
+        #[arg(long)]
+        weight: Option<u64>,
+    },
+    /// Remove owners from a chain, keeping the others
+    Remove {
+        #[arg(long)]
+        chain_id: String,
+        /// Owner to remove (repeatable)
+        #[arg(long = "owner", required = true)]
+        owners: Vec<String>,
+    },
+    /// Show the super owners, owners and weights of a chain
+    List { chain_id: String },
+}
+
+#[derive(Subcommand)]
+enum ChainCommand {
+    /// List the chains of the wallet
+    List,
+    /// Open a chain with a single owner
+    Open {
+        /// Chain paying for the new one [default: the wallet's default chain]
+        #[arg(long)]
+        from: Option<String>,
+        /// Owner of the new chain [default: a new key in the wallet]
+        #[arg(long)]
+        owner: Option<String>,
+        #[arg(long)]
+        initial_balance: Option<String>,
+    },
+    /// Open a chain with several owners
+    OpenMultiOwner {
+        #[arg(long)]
+        from: Option<String>,
+        /// Owner of the new chain (repeatable)
+        #[arg(long = "owner", required = true)]
+        owners: Vec<String>,
+        /// Weight of each owner, in the same order (repeatable)
+        #[arg(long = "owner-weight")]
+        owner_weights: Vec<u64>,
+        #[arg(long)]
+        multi_leader_rounds: Option<u32>,
+        #[arg(long)]
+        initial_balance: Option<String>,
+    },
+    /// Close a chain for good
+    Close { chain_id: String },
+}
+
+#[derive(Subcommand)]
+enum InstanceCommand {
+    /// List the default and named instances with their service state
+    List,
+    /// Create a named instance
+    Create {
+        name: String,
+        /// Wallet file on the server [default: ~/linera-wallet.<name>.json]
+        #[arg(long)]
+        wallet: Option<String>,
+        /// Keystore file on the server [default: ~/linera-keystore.<name>.json]
+        #[arg(long)]
+        keystore: Option<String>,
+        /// Storage used when a start request names none
+        #[arg(long)]
+        storage: Option<String>,
+        /// Port of the instance's service when a start request names none
+        #[arg(long)]
+        service_port: Option<u16>,
+    },
+    /// Show one instance
+    Show { name: String },
+    /// Delete a named instance whose service is not running
+    Delete { name: String },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommand {
+    /// Add or replace a profile
+    Set {
+        name: String,
+        #[arg(long)]
+        url: String,
+        #[arg(long)]
+        token: Option<String>,
+        /// Accept self-signed TLS certificates
+        #[arg(long)]
+        insecure: bool,
+        /// Use this profile when --profile is not given
+        #[arg(long)]
+        default: bool,
+    },
+    /// List saved profiles
+    List,
+    /// Delete a profile
+    Remove { name: String },
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Client for the selected profile, with `--url` and `--token` taking precedence
+fn client(profile: Option<&Profile>, url: Option<String>, token: Option<String>) -> Result<Client> {
+    let url = url
+        .or_else(|| profile.map(|p| p.url.clone()))
+        .unwrap_or_else(|| DEFAULT_URL.to_string());
+    let http = reqwest::Client::builder()
+        .danger_accept_invalid_certs(profile.is_some_and(|p| p.insecure))
+        .build()?;
+
+    let client = Client::new(url).with_http_client(http);
+    Ok(
+        match token.or_else(|| profile.and_then(|p| p.token.clone())) {
+            Some(token) => client.with_token(token),
+            None => client,
+        },
+    )
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let profiles_file = cli
+        .profiles_file
+        .clone()
+        .unwrap_or_else(Profiles::default_path);
+    let mut profiles = Profiles::load(&profiles_file)?;
+
+    if let Command::Profile(command) = cli.command {
+        return manage_profiles(command, &mut profiles, &profiles_file);
+    }
+
+    let client = client(profiles.select(cli.profile.as_deref())?, cli.url, cli.token)?;
+    let client = match &cli.instance {
+        Some(name) => client.instance(name),
+        None => client,
+    };
+
+    let data = match cli.command {
+        Command::Service(ServiceCommand::Start {
+            port,
+            storage,
+            startup_timeout,
+            restart,
+            max_restarts,
+        }) => {
+            let mut req = StartServiceRequest {
+                port,
+                storage,
+                ..Default::default()
+            };
+            if let Some(secs) = startup_timeout {
+                req.startup_timeout_secs = secs;
+            }
+            if let Some(mode) = restart {
+                req.restart.mode = mode.into();
+                req.restart.max_restarts = max_restarts;
+            }
+            to_value(client.start_service(&req).await?)
+        }
+        Command::Service(ServiceCommand::Stop { grace_period }) => {
+            let req = StopServiceRequest {
+                grace_period_secs: grace_period,
+            };
+            to_value(client.stop_service(&req).await?)
+        }
+        Command::Service(ServiceCommand::Status) => to_value(client.service_status().await?),
+        Command::Service(ServiceCommand::Logs { lines, stream }) => {
+            let lines = client
+                .service_logs(&LogsQuery {
+                    lines,
+                    stream: stream.map(Into::into),
+                })
+                .await?;
+            if cli.json {
+                to_value(lines)
+            } else {
+                for line in lines {
+                    println!("[{}] {}", line.stream.as_str(), line.line);
+                }
+                return Ok(());
+            }
+        }
+        Command::Wallet(WalletCommand::Init { faucet }) => {
+            to_value(client.init_wallet(&faucet).await?)
+        }
+        Command::Wallet(WalletCommand::RequestChain {
+            faucet,
+            set_default,
+        }) => {
+            let req = RequestChainRequest {
+                faucet_url: faucet,
+                set_default,
+            };
+            to_value(client.request_chain(&req).await?)
+        }
+        Command::Wallet(WalletCommand::Info) => to_value(client.wallet_info().await?),
+        Command::Wallet(WalletCommand::SetDefault { chain_id }) => {
+            to_value(client.set_default_chain(&chain_id).await?)
+        }
+        Command::Wallet(WalletCommand::Forget { chain_id }) => {
+            to_value(client.forget_chain(&chain_id).await?)
+        }
+        Command::Wallet(WalletCommand::Keygen) => to_value(client.keygen().await?),
+        Command::Owner(OwnerCommand::Add {
+            chain_id,
+            public_keys,
+            weight,
+        }) => {
+            let req = AddOwnerRequest {
+                chain_id,
+                public_keys,
+                weight,
+            };
+            to_value(client.add_owner(&req).await?)
+        }
+        Command::Owner(OwnerCommand::Remove { chain_id, owners }) => {
+            let req = RemoveOwnerRequest { chain_id, owners };
+            to_value(client.remove_owner(&req).await?)
+        }
+        Command::Owner(OwnerCommand::List { chain_id }) => {
+            to_value(client.chain_owners(&chain_id).await?)
+        }
+        Command::Chain(ChainCommand::List) => to_value(client.list_chains().await?),
+        Command::Chain(ChainCommand::Open {
+            from,
+            owner,
+            initial_balance,
+        }) => {
+            let req = OpenChainRequest {
+                from,
+                owner,
+                initial_balance,
+            };
+            to_value(client.open_chain(&req).await?)
+        }
+        Command::Chain(ChainCommand::OpenMultiOwner {
+            from,
+            owners,
+            owner_weights,
+            multi_leader_rounds,
+            initial_balance,
+        }) => {
+            let req = OpenMultiOwnerChainRequest {
+                from,
+                owners,
+                owner_weights: (!owner_weights.is_empty()).then_some(owner_weights),
+                multi_leader_rounds,
+                initial_balance,
+            };
+            to_value(client.open_multi_owner_chain(&req).await?)
+        }
+        Command::Chain(ChainCommand::Close { chain_id }) => {
+            to_value(client.close_chain(&chain_id).await?)
+        }
+        Command::Instance(InstanceCommand::List) => to_value(client.list_instances().await?),
+        Command::Instance(InstanceCommand::Create {
+            name,
+            wallet,
+            keystore,
+            storage,
+            service_port,
+        }) => {
+            let req = CreateInstanceRequest {
+                name,
+                wallet_path: wallet,
+                keystore_path: keystore,
+                storage,
+                service_port,
+            };
+            to_value(client.create_instance(&req).await?)
+        }
+        Command::Instance(InstanceCommand::Show { name }) => {
+            to_value(client.get_instance(&name).await?)
+        }
+        Command::Instance(InstanceCommand::Delete { name }) => {
+            to_value(client.delete_instance(&name).await?)
+        }
+        Command::Balance { chain_id, owners } => {
+            to_value(client.chain_balance(&chain_id, &owners).await?)
+        }
+        Command::Transfer { amount, from, to } => {
+            let req = TransferRequest { from, to, amount };
+            to_value(client.transfer(&req).await?)
+        }
+        Command::Graphql {
+            query,
+            chain_id,
+            app_id,
+            variables,
+        } => {
+            let variables = variables
+                .map(|v| serde_json::from_str::<Value>(&v))
+                .transpose()
+                .context("--variables is not valid JSON")?;
+            return graphql(&client, query, chain_id, app_id, variables).await;
+        }
+        Command::Health => {
+            client.health().await?;
+            json!({ "status": "ok" })
+        }
+        Command::Profile(_) => unreachable!(),
+    };
+
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&data)?);
+    } else {
+        print!("{}", render::render(&data));
+    }
+    Ok(())
+}
+
+fn to_value(data: impl serde::Serialize) -> Value {
+    serde_json::to_value(data).unwrap_or_default()
+}
+
+/// GraphQL answers are printed as-is; errors in them fail the command
+async fn graphql(
+    client: &Client,
+    query: String,
+    chain_id: Option<String>,
+    app_id: Option<String>,
+    variables: Option<Value>,
+) -> Result<()> {
+    let response = match chain_id {
+        Some(chain_id) => {
+            let req = GraphQLProxyRequest {
+                chain_id,
+                app_id,
+                query,
+                variables,
+            };
+            client.graphql(&req).await?
+        }
+        None => client.system_graphql(&query, variables).await?,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    if response.get("errors").is_some_and(|e| !e.is_null()) {
+        bail!("the query returned errors");
+    }
+    Ok(())
+}
+
+fn manage_profiles(
+    command: ProfileCommand,
+    profiles: &mut Profiles,
+    file: &std::path::Path,
+) -> Result<()> {
+    match command {
+        ProfileCommand::Set {
+            name,
+            url,
+            token,
+            insecure,
+            default,
+        } => {
+            if default || profiles.profiles.is_empty() {
+                profiles.default_profile = Some(name.clone());
+            }
+            profiles.profiles.insert(
+                name,
+                Profile {
+                    url,
+                    token,
+                    insecure,
+                },
+            );
+            profiles.save(file)
+        }
+        ProfileCommand::List => {
+            for (name, profile) in &profiles.profiles {
+                let marker = if profiles.default_profile.as_ref() == Some(name) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{} {}\t{}", marker, name, profile.url);
+            }
+            Ok(())
+        }
+        ProfileCommand::Remove { name } => {
+            if profiles.profiles.remove(&name).is_none() {
+                bail!("No profile named {:?}", name);
+            }
+            if profiles.default_profile.as_ref() == Some(&name) {
+                profiles.default_profile = None;
+            }
+            profiles.save(file)
+        }
+    }
+}
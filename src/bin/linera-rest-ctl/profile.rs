@@ -0,0 +1,72 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Server used when neither a flag nor a profile names one
+pub const DEFAULT_URL: &str = "http://localhost:3000";
+
+/// A server to talk to and the token to use with it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Accept self-signed TLS certificates
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure: bool,
+}
+
+/// Saved profiles, stored as TOML
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Profiles {
+    /// Profile used when `--profile` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    /// `~/.linera-rest-ctl.toml`
+    pub fn default_path() -> PathBuf {
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home).join(".linera-rest-ctl.toml")
+    }
+
+    /// Profiles saved in `path`; none if the file does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text)
+                .with_context(|| format!("Invalid profiles file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Write the profiles to `path`, readable only by us since they hold tokens
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        std::io::Write::write_all(
+            &mut options.open(path)?,
+            toml::to_string_pretty(self)?.as_bytes(),
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The profile named `name`, or the default one
+    pub fn select(&self, name: Option<&str>) -> Result<Option<&Profile>> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .map(Some)
+                .ok_or_else(|| anyhow!("No profile named {:?}", name)),
+            None => Ok(None),
+        }
+    }
+}
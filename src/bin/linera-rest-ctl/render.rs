@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+/// Human-readable form of a response's `data`: one `key: value` per line,
+/// nested objects and lists indented below their key
+pub fn render(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0);
+    out
+}
+
+fn write_value(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                write_entry(out, key, value, indent);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                push_indent(out, indent);
+                if is_scalar(item) {
+                    out.push_str(&format!("- {}\n", scalar(item)));
+                } else {
+                    out.push_str("-\n");
+                    write_value(out, item, indent + 1);
+                }
+            }
+        }
+        _ => {
+            push_indent(out, indent);
+            out.push_str(&scalar(value));
+            out.push('\n');
+        }
+    }
+}
+
+fn write_entry(out: &mut String, key: &str, value: &Value, indent: usize) {
+    push_indent(out, indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push_str(&format!("{}:\n", key));
+            write_value(out, value, indent + 1);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push_str(&format!("{}:\n", key));
+            write_value(out, value, indent + 1);
+        }
+        _ => out.push_str(&format!("{}: {}\n", key, scalar(value))),
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    out.push_str(&"  ".repeat(indent));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn nested_values_are_indented() {
+        let data = json!({
+            "chain_id": "abc",
+            "chains": [{ "chain_id": "abc", "owner": null }],
+            "last_exit": {},
+            "port": 8080
+        });
+
+        assert_eq!(
+            render(&data),
+            "chain_id: abc\nchains:\n  -\n    chain_id: abc\n    owner: -\nlast_exit: {}\nport: 8080\n"
+        );
+    }
+}
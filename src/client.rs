@@ -0,0 +1,301 @@
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::models::*;
+
+/// Why a request failed
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The server answered with an error response
+    #[error("{message} [{code}]")]
+    Api {
+        status: StatusCode,
+        code: ErrorCode,
+        message: String,
+        /// Structured details, e.g. the exit code and stderr of a service that failed to start
+        details: Option<Value>,
+    },
+    /// The server could not be reached or the connection failed
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The answer was not the JSON the API documents
+    #[error("Unexpected response ({status}): {body}")]
+    UnexpectedResponse { status: StatusCode, body: String },
+}
+
+impl ClientError {
+    /// Error code of an API error, `None` for transport errors
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            ClientError::Api { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T, E = ClientError> = std::result::Result<T, E>;
+
+/// Async client for a linera-rest-server.
+///
+/// Methods act on the default instance; use [`Client::instance`] for a named one.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    /// Path prefix of the selected instance, empty for the default one
+    prefix: String,
+}
+
+impl Client {
+    /// Client for the server at `base_url`, e.g. `http://localhost:3000`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            prefix: String::new(),
+        }
+    }
+
+    /// Send `token` as a bearer token with every request
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Send requests through `http`, e.g. one that accepts self-signed certificates
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// The same client acting on the named instance
+    pub fn instance(&self, name: &str) -> Self {
+        Self {
+            prefix: format!("/instances/{}", name),
+            ..self.clone()
+        }
+    }
+
+    pub async fn health(&self) -> Result<()> {
+        let response = self.request(Method::GET, "/health").send().await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(ClientError::UnexpectedResponse {
+                status,
+                body: response.text().await.unwrap_or_default(),
+            })
+        }
+    }
+
+    pub async fn start_service(&self, req: &StartServiceRequest) -> Result<ServiceStarted> {
+        self.call(self.post("/service/start").json(req)).await
+    }
+
+    pub async fn stop_service(&self, req: &StopServiceRequest) -> Result<StopOutcome> {
+        self.call(self.post("/service/stop").json(req)).await
+    }
+
+    pub async fn service_status(&self) -> Result<ServiceStatus> {
+        self.call(self.get("/service/status")).await
+    }
+
+    pub async fn service_logs(&self, query: &LogsQuery) -> Result<Vec<LogLine>> {
+        self.call(self.get("/service/logs").query(query)).await
+    }
+
+    pub async fn init_wallet(&self, faucet_url: &str) -> Result<WalletInfo> {
+        let req = InitWalletRequest {
+            faucet_url: faucet_url.to_string(),
+        };
+        self.call(self.post("/wallet/init").json(&req)).await
+    }
+
+    /// Request another chain from a faucet and add it to the existing wallet
+    pub async fn request_chain(&self, req: &RequestChainRequest) -> Result<WalletChain> {
+        self.call(self.post("/wallet/request-chain").json(req))
+            .await
+    }
+
+    pub async fn wallet_info(&self) -> Result<WalletInfo> {
+        self.call(self.get("/wallet/info")).await
+    }
+
+    pub async fn set_default_chain(&self, chain_id: &str) -> Result<WalletInfo> {
+        let req = SetDefaultChainRequest {
+            chain_id: chain_id.to_string(),
+        };
+        self.call(self.put("/wallet/default-chain").json(&req))
+            .await
+    }
+
+    /// Remove a chain from the wallet; the chain itself is not closed
+    pub async fn forget_chain(&self, chain_id: &str) -> Result<WalletInfo> {
+        self.call(self.delete(&format!("/wallet/chains/{}", chain_id)))
+            .await
+    }
+
+    pub async fn keygen(&self) -> Result<Keygen> {
+        self.call(self.post("/wallet/keygen")).await
+    }
+
+    /// Add owners to a chain; its current owners are kept
+    pub async fn add_owner(&self, req: &AddOwnerRequest) -> Result<OwnerAddResult> {
+        self.call(self.post("/owner/add").json(req)).await
+    }
+
+    pub async fn remove_owner(&self, req: &RemoveOwnerRequest) -> Result<ChainOwnership> {
+        self.call(self.post("/owner/remove").json(req)).await
+    }
+
+    /// Every chain the wallet tracks
+    pub async fn list_chains(&self) -> Result<Vec<WalletChain>> {
+        self.call(self.get("/chains")).await
+    }
+
+    pub async fn open_chain(&self, req: &OpenChainRequest) -> Result<ChainOpened> {
+        self.call(self.post("/chains/open").json(req)).await
+    }
+
+    pub async fn open_multi_owner_chain(
+        &self,
+        req: &OpenMultiOwnerChainRequest,
+    ) -> Result<ChainOpened> {
+        self.call(self.post("/chains/open-multi-owner").json(req))
+            .await
+    }
+
+    pub async fn close_chain(&self, chain_id: &str) -> Result<ChainClosed> {
+        self.call(self.post(&format!("/chains/{}/close", chain_id)))
+            .await
+    }
+
+    /// Super owners, owners and weights of a chain; needs the service to be running
+    pub async fn chain_owners(&self, chain_id: &str) -> Result<ChainOwnership> {
+        self.call(self.get(&format!("/chains/{}/owners", chain_id)))
+            .await
+    }
+
+    /// Balance of a chain and of `owners` on it; no owners means the wallet's owner of the chain
+    pub async fn chain_balance(&self, chain_id: &str, owners: &[String]) -> Result<ChainBalance> {
+        let query = BalanceQuery {
+            owners: (!owners.is_empty()).then(|| owners.join(",")),
+        };
+        self.call(
+            self.get(&format!("/chains/{}/balance", chain_id))
+                .query(&query),
+        )
+        .await
+    }
+
+    pub async fn transfer(&self, req: &TransferRequest) -> Result<TransferResult> {
+        self.call(self.post("/transfer").json(req)).await
+    }
+
+    /// Query a chain or application; GraphQL `errors` are left in the returned value
+    pub async fn graphql(&self, req: &GraphQLProxyRequest) -> Result<Value> {
+        self.call_raw(self.post("/graphql").json(req)).await
+    }
+
+    /// Query the service's system API
+    pub async fn system_graphql(&self, query: &str, variables: Option<Value>) -> Result<Value> {
+        let mut body = serde_json::json!({ "query": query });
+        if let Some(variables) = variables {
+            body["variables"] = variables;
+        }
+        self.call_raw(self.post("/graphql/system").json(&body))
+            .await
+    }
+
+    pub async fn list_instances(&self) -> Result<Vec<InstanceInfo>> {
+        self.call(self.request(Method::GET, "/instances")).await
+    }
+
+    pub async fn create_instance(&self, req: &CreateInstanceRequest) -> Result<InstanceInfo> {
+        self.call(self.request(Method::POST, "/instances").json(req))
+            .await
+    }
+
+    pub async fn get_instance(&self, name: &str) -> Result<InstanceInfo> {
+        self.call(self.request(Method::GET, &format!("/instances/{}", name)))
+            .await
+    }
+
+    pub async fn delete_instance(&self, name: &str) -> Result<InstanceRemoved> {
+        self.call(self.request(Method::DELETE, &format!("/instances/{}", name)))
+            .await
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        self.request(Method::GET, &format!("{}{}", self.prefix, path))
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        self.request(Method::POST, &format!("{}{}", self.prefix, path))
+    }
+
+    fn put(&self, path: &str) -> RequestBuilder {
+        self.request(Method::PUT, &format!("{}{}", self.prefix, path))
+    }
+
+    fn delete(&self, path: &str) -> RequestBuilder {
+        self.request(Method::DELETE, &format!("{}{}", self.prefix, path))
+    }
+
+    /// Request to `path` of the API version this client was built for
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!("{}{}{}", self.base_url, API_PREFIX, path);
+        let request = self.http.request(method, url);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Send `request` and unwrap the `data` of its [`ApiResponse`]
+    async fn call<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let (status, body) = send(request).await?;
+        let response: ApiResponse<Value> = parse(status, &body)?;
+
+        if !response.success {
+            return Err(api_error(status, response));
+        }
+        let data = response.data.unwrap_or(Value::Null);
+        serde_json::from_value(data).map_err(|_| ClientError::UnexpectedResponse { status, body })
+    }
+
+    /// Send `request` whose successful answer is not wrapped in an [`ApiResponse`]
+    async fn call_raw(&self, request: RequestBuilder) -> Result<Value> {
+        let (status, body) = send(request).await?;
+        if status.is_success() {
+            return parse(status, &body);
+        }
+        Err(api_error(status, parse(status, &body)?))
+    }
+}
+
+async fn send(request: RequestBuilder) -> Result<(StatusCode, String)> {
+    let response = request.send().await?;
+    let status = response.status();
+    Ok((status, response.text().await?))
+}
+
+fn parse<T: DeserializeOwned>(status: StatusCode, body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|_| ClientError::UnexpectedResponse {
+        status,
+        body: body.to_string(),
+    })
+}
+
+fn api_error(status: StatusCode, response: ApiResponse<Value>) -> ClientError {
+    ClientError::Api {
+        status,
+        code: response.code.unwrap_or(ErrorCode::Unknown),
+        message: response.error.unwrap_or_else(|| status.to_string()),
+        details: response.data,
+    }
+}
@@ -0,0 +1,423 @@
+use anyhow::{anyhow, bail, Context, Result};
+use axum::http::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::warn;
+
+use crate::linera_manager::{OrphanPolicy, DEFAULT_STOP_GRACE_PERIOD};
+use crate::service_logs;
+use crate::storage;
+
+/// Port the linera service listens on when a start request does not name one
+pub const DEFAULT_SERVICE_PORT: u16 = 8080;
+
+/// Server configuration, read from a TOML file and overridden by environment
+/// variables and command line flags (in that order)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Address the REST server binds to
+    pub bind: IpAddr,
+    /// Port the REST server listens on
+    pub port: u16,
+    /// Directory holding the web UI
+    pub web_dir: PathBuf,
+    /// Origins allowed to call the API from a browser; `*` allows any
+    pub cors_origins: Vec<String>,
+    /// Serve HTTPS instead of HTTP
+    pub tls: Option<TlsConfig>,
+    /// API tokens, as written by `gen-token --save`
+    pub tokens_file: PathBuf,
+    /// Named instances, saved so they survive restarts
+    pub instances_file: PathBuf,
+    /// The linera binary and the default instance's wallet
+    pub linera: LineraConfig,
+}
+
+/// PEM certificate chain and private key for HTTPS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LineraConfig {
+    /// Path to the linera binary
+    pub bin: PathBuf,
+    /// Wallet of the default instance
+    pub wallet: PathBuf,
+    /// Keystore of the default instance
+    pub keystore: PathBuf,
+    /// Port of `linera service` when a start request does not name one
+    pub service_port: u16,
+    /// Storage of `linera service` when a start request does not name one
+    pub storage: String,
+    /// Records the running service so a restarted server can find it
+    pub state_file: PathBuf,
+    /// What to do on boot with a service left running by a previous server
+    pub orphan_policy: OrphanPolicy,
+    /// Seconds between SIGTERM and SIGKILL when stopping the service
+    pub stop_grace_secs: u64,
+    /// Lines of service output kept in memory
+    pub log_buffer_lines: usize,
+    /// Also write service output to this file
+    pub service_log: Option<PathBuf>,
+    /// Size past which the service log is rotated to `.1`
+    pub service_log_max_bytes: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 3000,
+            web_dir: PathBuf::from("./web"),
+            cors_origins: vec!["*".to_string()],
+            tls: None,
+            tokens_file: home().join(".linera-rest-server-tokens.json"),
+            instances_file: home().join(".linera-rest-server-instances.json"),
+            linera: LineraConfig::default(),
+        }
+    }
+}
+
+impl Default for LineraConfig {
+    fn default() -> Self {
+        let home = home();
+        Self {
+            bin: home.join("bin/linera"),
+            wallet: home.join("linera-wallet.json"),
+            keystore: home.join("linera-keystore.json"),
+            service_port: DEFAULT_SERVICE_PORT,
+            storage: storage::DEFAULT_STORAGE.to_string(),
+            state_file: home.join(".linera-rest-server.json"),
+            orphan_policy: OrphanPolicy::Adopt,
+            stop_grace_secs: DEFAULT_STOP_GRACE_PERIOD.as_secs(),
+            log_buffer_lines: service_logs::DEFAULT_BUFFER_LINES,
+            service_log: None,
+            service_log_max_bytes: service_logs::DEFAULT_LOG_FILE_MAX_BYTES,
+        }
+    }
+}
+
+/// Settings given on the command line or through the environment, taking
+/// precedence over the config file; accepted before or after a subcommand
+#[derive(Debug, Default, clap::Args)]
+pub struct ConfigArgs {
+    /// Config file [default: ~/.linera-rest-server.toml, if it exists]
+    #[arg(long, global = true, env = "LINERA_REST_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Address to bind to
+    #[arg(long, global = true, env = "BIND_ADDRESS")]
+    pub bind: Option<IpAddr>,
+    /// Port to listen on
+    #[arg(long, global = true, env = "PORT")]
+    pub port: Option<u16>,
+    /// Directory holding the web UI
+    #[arg(long, global = true, env = "WEB_DIR")]
+    pub web_dir: Option<PathBuf>,
+    /// Origins allowed to call the API from a browser (comma separated, `*` for any)
+    #[arg(
+        long = "cors-origin",
+        global = true,
+        env = "CORS_ORIGINS",
+        value_delimiter = ','
+    )]
+    pub cors_origins: Option<Vec<String>>,
+    /// PEM certificate chain; serves HTTPS together with --tls-key
+    #[arg(long, global = true, env = "TLS_CERT")]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key for --tls-cert
+    #[arg(long, global = true, env = "TLS_KEY")]
+    pub tls_key: Option<PathBuf>,
+    /// API tokens file
+    #[arg(long, global = true, env = "LINERA_API_TOKENS_FILE")]
+    pub tokens_file: Option<PathBuf>,
+    /// Named instances file
+    #[arg(long, global = true, env = "LINERA_INSTANCES_FILE")]
+    pub instances_file: Option<PathBuf>,
+    /// Path to the linera binary
+    #[arg(long, global = true, env = "LINERA_BIN")]
+    pub linera_bin: Option<PathBuf>,
+    /// Wallet of the default instance
+    #[arg(long, global = true, env = "LINERA_WALLET")]
+    pub wallet: Option<PathBuf>,
+    /// Keystore of the default instance
+    #[arg(long, global = true, env = "LINERA_KEYSTORE")]
+    pub keystore: Option<PathBuf>,
+    /// Port of `linera service` when a start request does not name one
+    #[arg(long, global = true, env = "LINERA_SERVICE_PORT")]
+    pub service_port: Option<u16>,
+    /// Storage of `linera service` when a start request does not name one
+    #[arg(long, global = true, env = "LINERA_STORAGE")]
+    pub storage: Option<String>,
+    /// File recording the running service
+    #[arg(long, global = true, env = "LINERA_STATE_FILE")]
+    pub state_file: Option<PathBuf>,
+    /// What to do with a service left running by a previous server (adopt or kill)
+    #[arg(long, global = true, env = "LINERA_ORPHAN_POLICY")]
+    pub orphan_policy: Option<OrphanPolicy>,
+    /// Seconds between SIGTERM and SIGKILL when stopping the service
+    #[arg(long, global = true, env = "LINERA_STOP_GRACE_SECS")]
+    pub stop_grace_secs: Option<u64>,
+    /// Lines of service output kept in memory
+    #[arg(long, global = true, env = "LINERA_LOG_BUFFER_LINES")]
+    pub log_buffer_lines: Option<usize>,
+    /// Also write service output to this file
+    #[arg(long, global = true, env = "LINERA_SERVICE_LOG")]
+    pub service_log: Option<PathBuf>,
+    /// Size in bytes past which the service log is rotated
+    #[arg(long, global = true, env = "LINERA_SERVICE_LOG_MAX_BYTES")]
+    pub service_log_max_bytes: Option<u64>,
+}
+
+impl Config {
+    /// Read the config file, apply `args` on top and validate the result
+    pub fn load(args: ConfigArgs) -> Result<Self> {
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None => {
+                let path = home().join(".linera-rest-server.toml");
+                if path.exists() {
+                    Self::from_file(&path)?
+                } else {
+                    Self::default()
+                }
+            }
+        };
+
+        config.apply(args)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    fn apply(&mut self, args: ConfigArgs) -> Result<()> {
+        if let Some(bind) = args.bind {
+            self.bind = bind;
+        }
+        if let Some(port) = args.port {
+            self.port = port;
+        }
+        if let Some(web_dir) = args.web_dir {
+            self.web_dir = web_dir;
+        }
+        if let Some(origins) = args.cors_origins {
+            self.cors_origins = origins;
+        }
+        match (args.tls_cert, args.tls_key) {
+            (Some(cert), Some(key)) => self.tls = Some(TlsConfig { cert, key }),
+            (None, None) => {}
+            _ => bail!("--tls-cert and --tls-key must be given together"),
+        }
+        if let Some(tokens_file) = args.tokens_file {
+            self.tokens_file = tokens_file;
+        }
+        if let Some(instances_file) = args.instances_file {
+            self.instances_file = instances_file;
+        }
+        if let Some(bin) = args.linera_bin {
+            self.linera.bin = bin;
+        }
+        if let Some(wallet) = args.wallet {
+            self.linera.wallet = wallet;
+        }
+        if let Some(keystore) = args.keystore {
+            self.linera.keystore = keystore;
+        }
+        if let Some(port) = args.service_port {
+            self.linera.service_port = port;
+        }
+        if let Some(storage) = args.storage {
+            self.linera.storage = storage;
+        }
+        if let Some(state_file) = args.state_file {
+            self.linera.state_file = state_file;
+        }
+        if let Some(policy) = args.orphan_policy {
+            self.linera.orphan_policy = policy;
+        }
+        if let Some(secs) = args.stop_grace_secs {
+            self.linera.stop_grace_secs = secs;
+        }
+        if let Some(lines) = args.log_buffer_lines {
+            self.linera.log_buffer_lines = lines;
+        }
+        if let Some(path) = args.service_log {
+            self.linera.service_log = Some(path);
+        }
+        if let Some(bytes) = args.service_log_max_bytes {
+            self.linera.service_log_max_bytes = bytes;
+        }
+        Ok(())
+    }
+
+    /// Reject settings the server could not start with
+    pub fn validate(&self) -> Result<()> {
+        if self.linera.service_port == 0 {
+            bail!("linera.service_port must not be 0");
+        }
+        if self.linera.service_port == self.port {
+            bail!(
+                "linera.service_port and port are both {}; the service would clash with the REST server",
+                self.port
+            );
+        }
+        storage::validate(&self.linera.storage).map_err(|e| anyhow!("linera.storage: {}", e))?;
+        if self.linera.log_buffer_lines == 0 {
+            bail!("linera.log_buffer_lines must not be 0");
+        }
+
+        if self.cors_origins.iter().any(|o| o == "*") {
+            if self.cors_origins.len() > 1 {
+                bail!("cors_origins: \"*\" cannot be combined with other origins");
+            }
+        } else {
+            for origin in &self.cors_origins {
+                validate_origin(origin)?;
+            }
+        }
+
+        if let Some(tls) = &self.tls {
+            for (what, path) in [("certificate", &tls.cert), ("key", &tls.key)] {
+                if !path.is_file() {
+                    bail!("TLS {} {} does not exist", what, path.display());
+                }
+            }
+        }
+
+        if !self.web_dir.is_dir() {
+            warn!("Web UI directory {} does not exist", self.web_dir.display());
+        }
+        if !self.linera.bin.exists() {
+            warn!("linera binary {} does not exist", self.linera.bin.display());
+        }
+        Ok(())
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind, self.port)
+    }
+
+    /// CORS policy allowing the configured origins
+    pub fn cors_layer(&self) -> CorsLayer {
+        let layer = CorsLayer::new().allow_methods(Any).allow_headers(Any);
+
+        if self.cors_origins.iter().any(|o| o == "*") {
+            return layer.allow_origin(Any);
+        }
+
+        let origins = self
+            .cors_origins
+            .iter()
+            .filter_map(|o| HeaderValue::from_str(o).ok())
+            .collect::<Vec<_>>();
+        layer.allow_origin(AllowOrigin::list(origins))
+    }
+}
+
+/// `scheme://host[:port]`, as browsers send it in the `Origin` header
+fn validate_origin(origin: &str) -> Result<()> {
+    let invalid = |reason: &str| anyhow!("cors_origins: invalid origin {:?}: {}", origin, reason);
+
+    let (scheme, rest) = origin
+        .split_once("://")
+        .ok_or_else(|| invalid("expected scheme://host[:port]"))?;
+    if scheme != "http" && scheme != "https" {
+        return Err(invalid("scheme must be http or https"));
+    }
+    if rest.is_empty() || rest.contains('/') {
+        return Err(invalid("must not have a path or trailing slash"));
+    }
+    HeaderValue::from_str(origin).map_err(|_| invalid("not a valid header value"))?;
+    Ok(())
+}
+
+fn home() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| ".".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_values_are_overridden_by_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config.toml");
+        std::fs::write(
+            &file,
+            r#"
+            bind = "127.0.0.1"
+            port = 4000
+            cors_origins = ["https://app.example.com"]
+
+            [linera]
+            bin = "/opt/linera"
+            service_port = 9000
+            orphan_policy = "kill"
+            stop_grace_secs = 30
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::load(ConfigArgs {
+            config: Some(file),
+            port: Some(5000),
+            stop_grace_secs: Some(5),
+            service_log: Some(PathBuf::from("/tmp/linera.log")),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(config.addr(), "127.0.0.1:5000".parse().unwrap());
+        assert_eq!(config.cors_origins, ["https://app.example.com"]);
+        assert_eq!(config.linera.bin, PathBuf::from("/opt/linera"));
+        assert_eq!(config.linera.service_port, 9000);
+        assert!(config.linera.wallet.ends_with("linera-wallet.json"));
+        assert_eq!(config.linera.orphan_policy, OrphanPolicy::Kill);
+        assert_eq!(config.linera.stop_grace_secs, 5);
+        assert_eq!(
+            config.linera.service_log,
+            Some(PathBuf::from("/tmp/linera.log"))
+        );
+        assert_eq!(config.linera.storage, "memory");
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let invalid = |toml: &str| {
+            let config: Config = toml::from_str(toml).unwrap();
+            config.validate().unwrap_err().to_string()
+        };
+
+        assert!(invalid(r#"cors_origins = ["https://a.example", "*"]"#).contains("\"*\""));
+        assert!(invalid(r#"cors_origins = ["https://a.example/"]"#).contains("trailing slash"));
+        assert!(invalid(r#"cors_origins = ["a.example"]"#).contains("scheme://"));
+        assert!(
+            invalid("tls = { cert = \"/missing.pem\", key = \"/missing.key\" }")
+                .contains("/missing.pem")
+        );
+        assert!(invalid("port = 8080").contains("clash"));
+        assert!(invalid("[linera]\nlog_buffer_lines = 0").contains("log_buffer_lines"));
+        assert!(invalid("[linera]\nstorage = \"\"").contains("linera.storage"));
+
+        assert!(toml::from_str::<Config>("[linera]\norphan_policy = \"keep\"").is_err());
+        assert!(toml::from_str::<Config>("prot = 3000").is_err());
+        assert!(Config::load(ConfigArgs {
+            tls_cert: Some(PathBuf::from("cert.pem")),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use thiserror::Error;
+
+use crate::models::{ApiResponse, ErrorCode, ServiceStartFailure};
+
+/// Errors reported to API clients, each with a status code and a stable `code` string.
+///
+/// Internals keep returning `anyhow::Result` and raise these with `.into()`;
+/// converting back with `?` in a handler recovers the variant.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    InvalidRequest(String),
+
+    #[error("Invalid chain ID {0:?}: expected 64 hex characters")]
+    InvalidChainId(String),
+
+    #[error("{0}")]
+    InvalidStorage(String),
+
+    #[error("Invalid owner {0:?}: expected 0x and 40 or 64 hex characters")]
+    InvalidOwner(String),
+
+    #[error("Invalid amount {0:?}: expected a positive decimal with at most 18 decimals")]
+    InvalidAmount(String),
+
+    #[error("Wallet not initialized: {0} does not exist")]
+    WalletNotInitialized(String),
+
+    #[error("Wallet already exists at {0}")]
+    WalletAlreadyExists(String),
+
+    #[error("Chain {0} is not in the wallet")]
+    ChainNotInWallet(String),
+
+    #[error("Missing or unknown API token")]
+    Unauthorized,
+
+    #[error("Token {0} is read-only; this endpoint needs an admin token")]
+    Forbidden(String),
+
+    #[error("Instance {0} not found")]
+    InstanceNotFound(String),
+
+    #[error("Instance {0} already exists")]
+    InstanceAlreadyExists(String),
+
+    #[error("Instance {0} is running; stop it first")]
+    InstanceRunning(String),
+
+    #[error("Linera service is already running")]
+    ServiceAlreadyRunning,
+
+    #[error("Linera service is not running")]
+    ServiceNotRunning,
+
+    #[error("Port {0} is already in use")]
+    PortInUse(u16),
+
+    #[error("Linera binary not found: {0}")]
+    BinaryMissing(String),
+
+    #[error(transparent)]
+    ServiceStartFailed(#[from] ServiceStartFailure),
+
+    #[error("{0}")]
+    CommandFailed(String),
+
+    #[error("GraphQL request failed: {0}")]
+    GraphQL(String),
+
+    #[error("GraphQL request timed out")]
+    GraphQLTimeout,
+
+    #[error("Faucet unreachable: {0}")]
+    FaucetUnreachable(String),
+
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidRequest(_)
+            | ApiError::InvalidChainId(_)
+            | ApiError::InvalidStorage(_)
+            | ApiError::InvalidOwner(_)
+            | ApiError::InvalidAmount(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::WalletNotInitialized(_)
+            | ApiError::ChainNotInWallet(_)
+            | ApiError::InstanceNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::WalletAlreadyExists(_)
+            | ApiError::InstanceAlreadyExists(_)
+            | ApiError::InstanceRunning(_)
+            | ApiError::ServiceAlreadyRunning
+            | ApiError::PortInUse(_) => StatusCode::CONFLICT,
+            ApiError::ServiceNotRunning | ApiError::BinaryMissing(_) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            ApiError::GraphQL(_) | ApiError::FaucetUnreachable(_) => StatusCode::BAD_GATEWAY,
+            ApiError::GraphQLTimeout => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::ServiceStartFailed(_)
+            | ApiError::CommandFailed(_)
+            | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine-readable identifier that stays the same when messages are reworded
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            ApiError::InvalidChainId(_) => ErrorCode::InvalidChainId,
+            ApiError::InvalidStorage(_) => ErrorCode::InvalidStorage,
+            ApiError::InvalidOwner(_) => ErrorCode::InvalidOwner,
+            ApiError::InvalidAmount(_) => ErrorCode::InvalidAmount,
+            ApiError::WalletNotInitialized(_) => ErrorCode::WalletNotInitialized,
+            ApiError::WalletAlreadyExists(_) => ErrorCode::WalletAlreadyExists,
+            ApiError::ChainNotInWallet(_) => ErrorCode::ChainNotInWallet,
+            ApiError::Unauthorized => ErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::InstanceNotFound(_) => ErrorCode::InstanceNotFound,
+            ApiError::InstanceAlreadyExists(_) => ErrorCode::InstanceAlreadyExists,
+            ApiError::InstanceRunning(_) => ErrorCode::InstanceRunning,
+            ApiError::ServiceAlreadyRunning => ErrorCode::ServiceAlreadyRunning,
+            ApiError::ServiceNotRunning => ErrorCode::ServiceNotRunning,
+            ApiError::PortInUse(_) => ErrorCode::PortInUse,
+            ApiError::BinaryMissing(_) => ErrorCode::BinaryMissing,
+            ApiError::ServiceStartFailed(_) => ErrorCode::ServiceStartFailed,
+            ApiError::CommandFailed(_) => ErrorCode::CommandFailed,
+            ApiError::GraphQL(_) => ErrorCode::GraphQL,
+            ApiError::GraphQLTimeout => ErrorCode::GraphQLTimeout,
+            ApiError::FaucetUnreachable(_) => ErrorCode::FaucetUnreachable,
+            ApiError::Internal(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<ApiError>() {
+            Ok(api_error) => return api_error,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<ServiceStartFailure>() {
+            Ok(failure) => return ApiError::ServiceStartFailed(failure),
+            Err(e) => e,
+        };
+        // reqwest is only used to talk to the linera service's GraphQL endpoint
+        match e.downcast::<reqwest::Error>() {
+            Ok(e) if e.is_timeout() => ApiError::GraphQLTimeout,
+            Ok(e) => ApiError::GraphQL(e.to_string()),
+            Err(e) => ApiError::Internal(e),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let message = self.to_string();
+
+        let body = match &self {
+            ApiError::ServiceStartFailed(failure) => {
+                ApiResponse::failure(message, serde_json::to_value(failure).unwrap_or_default())
+            }
+            _ => ApiResponse::error(message),
+        };
+
+        let mut response = (status, Json(body.with_code(self.code()))).into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
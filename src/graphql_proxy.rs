@@ -1,12 +1,22 @@
 use anyhow::Result;
-use reqwest::Client;
+use reqwest::{Client, Response};
 use serde_json::Value;
+use std::time::Duration;
 use tracing::{error, info};
 
+use crate::error::ApiError;
+
+/// Timeout for a single health check request
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default timeout for a proxied query
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// GraphQL proxy for forwarding requests to linera service
 pub struct GraphQLProxy {
     client: Client,
     base_url: String,
+    query_timeout: Duration,
 }
 
 impl GraphQLProxy {
@@ -14,9 +24,16 @@ impl GraphQLProxy {
         Self {
             client: Client::new(),
             base_url: format!("http://localhost:{}", port),
+            query_timeout: DEFAULT_QUERY_TIMEOUT,
         }
     }
 
+    /// Give up on queries that take longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.query_timeout = timeout;
+        self
+    }
+
     /// Proxy a GraphQL query to the linera service
     pub async fn query(
         &self,
@@ -47,18 +64,12 @@ impl GraphQLProxy {
         let response = self.client
             .post(&url)
             .header("Content-Type", "application/json")
+            .timeout(self.query_timeout)
             .json(&body)
             .send()
             .await?;
 
-        let status = response.status();
-        let response_body: Value = response.json().await?;
-
-        if !status.is_success() {
-            error!("GraphQL request failed with status {}: {:?}", status, response_body);
-        }
-
-        Ok(response_body)
+        read_response(response).await
     }
 
     /// Query the system API (no chain/app)
@@ -78,20 +89,84 @@ impl GraphQLProxy {
         let response = self.client
             .post(&url)
             .header("Content-Type", "application/json")
+            .timeout(self.query_timeout)
             .json(&body)
             .send()
             .await?;
 
-        Ok(response.json().await?)
+        read_response(response).await
     }
 
     /// Check if the linera service is reachable
     pub async fn health_check(&self) -> bool {
         let url = format!("{}/", self.base_url);
         
-        match self.client.get(&url).send().await {
+        match self.client.get(&url).timeout(HEALTH_CHECK_TIMEOUT).send().await {
             Ok(resp) => resp.status().is_success(),
             Err(_) => false,
         }
     }
 }
+
+/// JSON body of a successful response; GraphQL-level `errors` are left for the client
+async fn read_response(response: Response) -> Result<Value> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        error!("GraphQL request failed with status {}: {}", status, body);
+        return Err(
+            ApiError::GraphQL(format!("service answered {}: {}", status, body.trim())).into(),
+        );
+    }
+
+    Ok(response.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockGraphQL, MockResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn slow_answers_within_timeout_succeed() {
+        let mock = MockGraphQL::new().respond(
+            "/",
+            MockResponse::json(json!({ "data": { "version": "0.15.0" } }))
+                .delay(Duration::from_millis(100)),
+        );
+        let proxy = GraphQLProxy::new(mock.start().await).with_timeout(Duration::from_secs(5));
+
+        let result = proxy.system_query("{ version }", None).await.unwrap();
+
+        assert_eq!(result["data"]["version"], "0.15.0");
+    }
+
+    #[tokio::test]
+    async fn slow_answers_past_timeout_fail() {
+        let mock = MockGraphQL::new().respond(
+            "/",
+            MockResponse::json(json!({ "data": {} })).delay(Duration::from_secs(5)),
+        );
+        let proxy =
+            GraphQLProxy::new(mock.start().await).with_timeout(Duration::from_millis(100));
+
+        let err = proxy.system_query("{ version }", None).await.unwrap_err();
+
+        assert!(matches!(ApiError::from(err), ApiError::GraphQLTimeout));
+    }
+
+    #[tokio::test]
+    async fn health_check_needs_graphiql_page() {
+        let mock = MockGraphQL::new();
+        assert!(GraphQLProxy::new(mock.start().await).health_check().await);
+
+        // Nothing listens on a port that was just released
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        assert!(!GraphQLProxy::new(port).health_check().await);
+    }
+}
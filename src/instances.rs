@@ -0,0 +1,350 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, Path},
+    http::request::Parts,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+use crate::accounts;
+use crate::error::ApiError;
+use crate::graphql_proxy::GraphQLProxy;
+use crate::linera_manager::LineraManager;
+use crate::models::{
+    AddOwnerRequest, ChainOwnership, CreateInstanceRequest, InstanceInfo, OwnerAddResult,
+    RemoveOwnerRequest, ServiceState, TransferRequest, TransferResult,
+};
+use crate::ownership;
+use crate::AppState;
+
+/// Name under which the instance configured from the environment is reachable
+pub const DEFAULT_INSTANCE: &str = "default";
+
+/// A linera wallet with its own service process and GraphQL proxy
+pub struct Instance {
+    pub name: String,
+    pub manager: LineraManager,
+    pub graphql_proxy: RwLock<Option<GraphQLProxy>>,
+    /// Held from reading a chain's ownership until the change is submitted, so
+    /// concurrent owner changes do not overwrite each other
+    ownership_lock: Mutex<()>,
+}
+
+impl Instance {
+    pub fn new(name: impl Into<String>, manager: LineraManager) -> Self {
+        Self {
+            name: name.into(),
+            manager,
+            graphql_proxy: RwLock::new(None),
+            ownership_lock: Mutex::new(()),
+        }
+    }
+
+    /// Pick up a service left running by a previous server and point the proxy at it
+    pub async fn reconcile(&self) {
+        self.manager.reconcile().await;
+        if let Some(port) = self.manager.get_port().await {
+            *self.graphql_proxy.write().await = Some(GraphQLProxy::new(port));
+        }
+    }
+
+    /// Current ownership of `chain_id`, which needs the service to be running
+    pub async fn chain_ownership(&self, chain_id: &str) -> Result<ChainOwnership> {
+        let proxy = self.graphql_proxy.read().await;
+        let proxy = proxy.as_ref().ok_or(ApiError::ServiceNotRunning)?;
+        ownership::query(proxy, chain_id).await
+    }
+
+    /// Add owners to a chain without dropping the ones it has
+    pub async fn add_owners(&self, req: AddOwnerRequest) -> Result<OwnerAddResult> {
+        crate::wallet::validate_chain_id(&req.chain_id)?;
+        ownership::validate_owners(&req.public_keys)?;
+        let _guard = self.ownership_lock.lock().await;
+        let current = self.chain_ownership(&req.chain_id).await?;
+        let weight = req.weight.unwrap_or(ownership::DEFAULT_OWNER_WEIGHT);
+        let updated = ownership::with_owners(&current, &req.public_keys, weight);
+        if updated != current {
+            self.manager.change_ownership(&updated).await?;
+        }
+
+        Ok(OwnerAddResult {
+            success: true,
+            chain_id: req.chain_id,
+            owners: req.public_keys,
+            ownership: updated,
+        })
+    }
+
+    /// Remove owners from a chain, keeping the others
+    pub async fn remove_owners(&self, req: RemoveOwnerRequest) -> Result<ChainOwnership> {
+        crate::wallet::validate_chain_id(&req.chain_id)?;
+        ownership::validate_owners(&req.owners)?;
+        let _guard = self.ownership_lock.lock().await;
+        let current = self.chain_ownership(&req.chain_id).await?;
+        let updated = ownership::without_owners(&current, &req.owners)?;
+        self.manager.change_ownership(&updated).await?;
+        Ok(updated)
+    }
+
+    /// Transfer tokens through the running service, which reports the certificate hash,
+    /// or with the CLI, which does not
+    pub async fn transfer(&self, req: TransferRequest) -> Result<TransferResult> {
+        let proxy = self.graphql_proxy.read().await;
+        let Some(proxy) = proxy.as_ref() else {
+            return self.manager.transfer(req).await;
+        };
+
+        accounts::validate_account(&req.from)?;
+        accounts::validate_account(&req.to)?;
+        accounts::validate_amount(&req.amount)?;
+        info!(
+            "Transferring {} from {} to {} through the service",
+            req.amount, req.from, req.to
+        );
+
+        let certificate_hash = accounts::transfer(proxy, &req).await?;
+        Ok(TransferResult {
+            from: req.from,
+            to: req.to,
+            amount: req.amount,
+            certificate_hash: Some(certificate_hash),
+        })
+    }
+
+    pub async fn info(&self) -> InstanceInfo {
+        InstanceInfo {
+            name: self.name.clone(),
+            wallet_path: self.manager.wallet_path().to_string(),
+            keystore_path: self.manager.keystore_path().to_string(),
+            default_storage: self.manager.default_storage().to_string(),
+            service_port: self.manager.service_port(),
+            status: self.manager.status().await,
+        }
+    }
+}
+
+/// Instance named by the `:name` path segment, or the default instance on unprefixed routes
+pub struct SelectedInstance(pub Arc<Instance>);
+
+impl std::ops::Deref for SelectedInstance {
+    type Target = Instance;
+
+    fn deref(&self) -> &Instance {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for SelectedInstance {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let params = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .ok();
+
+        let Some(name) = params.as_ref().and_then(|p| p.get("name")) else {
+            return Ok(SelectedInstance(state.instances.default_instance().clone()));
+        };
+
+        match state.instances.get(name).await {
+            Some(instance) => Ok(SelectedInstance(instance)),
+            None => Err(ApiError::InstanceNotFound(name.clone())),
+        }
+    }
+}
+
+/// Instance definition as persisted to the instances file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InstanceDefinition {
+    wallet_path: String,
+    keystore_path: String,
+    #[serde(default)]
+    storage: Option<String>,
+    #[serde(default)]
+    service_port: Option<u16>,
+}
+
+/// Named instances besides the default one, persisted so they survive server restarts
+pub struct InstanceRegistry {
+    default: Arc<Instance>,
+    named: RwLock<BTreeMap<String, (InstanceDefinition, Arc<Instance>)>>,
+    file: PathBuf,
+}
+
+impl InstanceRegistry {
+    /// Registry around `default`, persisting named instances to `file`
+    pub fn new(default: Arc<Instance>, file: PathBuf) -> Self {
+        Self {
+            default,
+            named: RwLock::new(BTreeMap::new()),
+            file,
+        }
+    }
+
+    pub fn default_instance(&self) -> &Arc<Instance> {
+        &self.default
+    }
+
+    /// Load instances saved by a previous run and reconcile their services
+    pub async fn load(&self) {
+        self.default.reconcile().await;
+
+        let definitions: BTreeMap<String, InstanceDefinition> = match std::fs::read(&self.file) {
+            Ok(json) => match serde_json::from_slice(&json) {
+                Ok(definitions) => definitions,
+                Err(e) => {
+                    warn!(
+                        "Ignoring unreadable instances file {}: {}",
+                        self.file.display(),
+                        e
+                    );
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+
+        let mut named = self.named.write().await;
+        for (name, definition) in definitions {
+            let instance = Arc::new(self.build(&name, &definition));
+            instance.reconcile().await;
+            info!("Loaded instance {}", name);
+            named.insert(name, (definition, instance));
+        }
+    }
+
+    pub async fn get(&self, name: &str) -> Option<Arc<Instance>> {
+        if name == DEFAULT_INSTANCE {
+            return Some(self.default.clone());
+        }
+        self.named.read().await.get(name).map(|(_, i)| i.clone())
+    }
+
+    /// Default instance first, then named ones in alphabetical order
+    pub async fn all(&self) -> Vec<Arc<Instance>> {
+        let named = self.named.read().await;
+        std::iter::once(self.default.clone())
+            .chain(named.values().map(|(_, i)| i.clone()))
+            .collect()
+    }
+
+    pub async fn create(&self, req: CreateInstanceRequest) -> Result<Arc<Instance>> {
+        validate_name(&req.name)?;
+        if let Some(storage) = &req.storage {
+            crate::storage::validate(storage)?;
+        }
+        if req.service_port == Some(0) {
+            return Err(ApiError::InvalidRequest("service_port must not be 0".to_string()).into());
+        }
+
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        let definition = InstanceDefinition {
+            wallet_path: req
+                .wallet_path
+                .unwrap_or_else(|| format!("{}/linera-wallet.{}.json", home, req.name)),
+            keystore_path: req
+                .keystore_path
+                .unwrap_or_else(|| format!("{}/linera-keystore.{}.json", home, req.name)),
+            storage: req.storage,
+            service_port: req.service_port,
+        };
+
+        let mut named = self.named.write().await;
+        if req.name == DEFAULT_INSTANCE || named.contains_key(&req.name) {
+            return Err(ApiError::InstanceAlreadyExists(req.name).into());
+        }
+        if let Some(port) = definition.service_port {
+            let taken = self.default.manager.service_port() == Some(port)
+                || named.values().any(|(d, _)| d.service_port == Some(port));
+            if taken {
+                return Err(ApiError::PortInUse(port).into());
+            }
+        }
+
+        let instance = Arc::new(self.build(&req.name, &definition));
+        named.insert(req.name.clone(), (definition, instance.clone()));
+        self.save(&named)?;
+
+        info!("Created instance {}", req.name);
+        Ok(instance)
+    }
+
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        if name == DEFAULT_INSTANCE {
+            return Err(ApiError::InvalidRequest(
+                "The default instance cannot be removed".to_string(),
+            )
+            .into());
+        }
+
+        let mut named = self.named.write().await;
+        let (_, instance) = named
+            .get(name)
+            .ok_or_else(|| ApiError::InstanceNotFound(name.to_string()))?;
+        if matches!(
+            instance.manager.status().await.state,
+            ServiceState::Starting | ServiceState::Running | ServiceState::Backoff
+        ) {
+            return Err(ApiError::InstanceRunning(name.to_string()).into());
+        }
+
+        // A later instance of the same name must not adopt a stale PID
+        instance.manager.clear_record();
+        named.remove(name);
+        self.save(&named)?;
+
+        info!("Removed instance {}", name);
+        Ok(())
+    }
+
+    /// Named instances run the same linera binary as the default one, keep
+    /// their state file next to its and treat orphans the same way
+    fn build(&self, name: &str, definition: &InstanceDefinition) -> Instance {
+        Instance::new(
+            name,
+            self.default
+                .manager
+                .for_instance(
+                    name,
+                    definition.wallet_path.clone(),
+                    definition.keystore_path.clone(),
+                    definition.storage.clone(),
+                )
+                .with_service_port(definition.service_port),
+        )
+    }
+
+    fn save(&self, named: &BTreeMap<String, (InstanceDefinition, Arc<Instance>)>) -> Result<()> {
+        let definitions: BTreeMap<_, _> = named.iter().map(|(name, (d, _))| (name, d)).collect();
+        std::fs::write(&self.file, serde_json::to_vec_pretty(&definitions)?)
+            .map_err(|e| anyhow!("Failed to write {}: {}", self.file.display(), e))
+    }
+}
+
+/// Instance names end up in file names and URLs
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidRequest(format!(
+            "Invalid instance name {:?}: use 1-64 letters, digits, '-' or '_'",
+            name
+        ))
+        .into())
+    }
+}
@@ -0,0 +1,16 @@
+//! Request and response types of the linera-rest-server API, and a typed
+//! async client for it.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), linera_rest_server::client::ClientError> {
+//! use linera_rest_server::client::Client;
+//!
+//! let client = Client::new("http://localhost:3000").with_token("secret");
+//! let wallet = client.wallet_info().await?;
+//! println!("default chain {}", wallet.chain_id);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+pub mod models;
@@ -0,0 +1,78 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::process::Stdio;
+use tokio::process::{Child, Command};
+
+use crate::error::ApiError;
+
+/// Captured result of a linera command that ran to completion
+#[derive(Debug, Clone, Default)]
+pub struct CliOutput {
+    /// Exit code, `None` if the process was killed by a signal
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CliOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// The linera command line, so the manager can run against something other than the real binary
+#[async_trait]
+pub trait LineraCli: Send + Sync {
+    /// Run a command such as `wallet show` or `keygen` and wait for it to finish
+    async fn run(&self, args: &[String]) -> Result<CliOutput>;
+
+    /// Start `linera service` with its stdout and stderr piped
+    fn spawn_service(&self, args: &[String]) -> Result<Child>;
+}
+
+/// Runs the linera binary at a given path
+pub struct ProcessCli {
+    bin: String,
+}
+
+impl ProcessCli {
+    pub fn new(bin: impl Into<String>) -> Self {
+        Self { bin: bin.into() }
+    }
+
+    /// Error for a command that could not be started at all
+    fn spawn_error(&self, e: std::io::Error) -> anyhow::Error {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            ApiError::BinaryMissing(self.bin.clone()).into()
+        } else {
+            anyhow!("Failed to run {}: {}", self.bin, e)
+        }
+    }
+}
+
+#[async_trait]
+impl LineraCli for ProcessCli {
+    async fn run(&self, args: &[String]) -> Result<CliOutput> {
+        let output = Command::new(&self.bin)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| self.spawn_error(e))?;
+
+        Ok(CliOutput {
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+
+    fn spawn_service(&self, args: &[String]) -> Result<Child> {
+        Command::new(&self.bin)
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| self.spawn_error(e))
+    }
+}
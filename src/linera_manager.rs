@@ -1,46 +1,246 @@
 use anyhow::{anyhow, Result};
-use std::process::Stdio;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::process::{Child, Command};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::process::Child;
 use tokio::sync::RwLock;
-use tracing::{error, info};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
 
-use crate::models::{OwnerAddResult, WalletInfo};
+use crate::accounts;
+use crate::config::LineraConfig;
+use crate::error::ApiError;
+use crate::graphql_proxy::GraphQLProxy;
+use crate::linera_cli::{LineraCli, ProcessCli};
+use crate::models::{
+    Account, ChainBalance, ChainClosed, ChainOpened, ChainOwnership, ExitInfo, LogStream,
+    OpenChainRequest, OpenMultiOwnerChainRequest, OwnerBalance, RequestChainRequest, RestartMode,
+    RestartPolicy, ServiceStartFailure, ServiceState, ServiceStatus, StopOutcome, TransferRequest,
+    TransferResult, WalletChain, WalletInfo,
+};
+use crate::service_logs::{self, ServiceLogs};
+use crate::service_process::{self, ServiceProcess};
+use crate::storage;
 
 /// Default faucet URL for Linera testnet
 pub const DEFAULT_FAUCET_URL: &str = "https://faucet.testnet-conway.linera.net";
 
+/// Time a faucet has to answer before a wallet command is sent to it
+const FAUCET_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Interval between readiness probes while the service is starting
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Number of trailing stderr lines reported when startup fails
+const STDERR_TAIL_LINES: usize = 20;
+
+/// How long to wait for the log readers to reach EOF after the service exits
+const LOG_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Interval at which the supervisor checks whether the service is still alive
+const SUPERVISOR_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Uptime after which a crash no longer counts towards consecutive restarts
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// Default time between SIGTERM and SIGKILL when stopping the service
+pub const DEFAULT_STOP_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// What to do on boot with a service left running by a previous server instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrphanPolicy {
+    /// Manage it as if we had started it
+    Adopt,
+    /// Stop it so we start from a clean slate
+    Kill,
+}
+
+impl std::str::FromStr for OrphanPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "adopt" => Ok(OrphanPolicy::Adopt),
+            "kill" => Ok(OrphanPolicy::Kill),
+            other => Err(anyhow!("Unknown orphan policy: {}", other)),
+        }
+    }
+}
+
+/// Everything needed to launch (and relaunch) the linera service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    pub port: u16,
+    pub startup_timeout_secs: u64,
+    pub restart: RestartPolicy,
+    /// Value passed to `--storage`
+    pub storage: String,
+}
+
+impl ServiceConfig {
+    fn startup_timeout(&self) -> Duration {
+        Duration::from_secs(self.startup_timeout_secs)
+    }
+}
+
+/// Running service as persisted to the state file
+#[derive(Debug, Serialize, Deserialize)]
+struct ServiceRecord {
+    pid: u32,
+    args: Vec<String>,
+    config: ServiceConfig,
+    started_at_ms: u64,
+}
+
+/// Supervisor bookkeeping about the service lifecycle
+#[derive(Default)]
+struct Supervision {
+    state: ServiceState,
+    restart_count: u32,
+    last_exit: Option<ExitInfo>,
+    /// When the current process started, in milliseconds since the epoch
+    started_at_ms: Option<u64>,
+    /// Bumped by every stop, so a start that was launching meanwhile knows to back out
+    generation: u64,
+}
+
 /// Manages the Linera service process and wallet operations
+#[derive(Clone)]
 pub struct LineraManager {
-    /// Path to the linera binary
-    linera_bin: String,
+    /// The linera command line
+    cli: Arc<dyn LineraCli>,
     /// Current service process (if running)
-    service_process: Arc<RwLock<Option<Child>>>,
-    /// Port the service is running on
-    service_port: Arc<RwLock<Option<u16>>>,
+    service_process: Arc<RwLock<Option<ServiceProcess>>>,
+    /// Configuration of the managed service (port, storage, ...)
+    service_config: Arc<RwLock<Option<ServiceConfig>>>,
     /// Path to wallet file
     wallet_path: String,
     /// Path to keystore file
     keystore_path: String,
+    /// Captured stdout/stderr of the service
+    logs: Arc<ServiceLogs>,
+    /// Tasks draining the service's output pipes
+    log_tasks: Arc<RwLock<Vec<JoinHandle<()>>>>,
+    /// Lifecycle state maintained by the supervisor
+    supervision: Arc<RwLock<Supervision>>,
+    /// Task watching and restarting the service process
+    supervisor: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// Time allowed for a graceful exit before the service is killed
+    stop_grace_period: Duration,
+    /// Storage used when a start request does not name one
+    default_storage: String,
+    /// File recording the running service so a restarted server can find it
+    state_file: PathBuf,
+    /// Default port of the service, `None` to use the server-wide default
+    service_port: Option<u16>,
+    /// What to do with a service found running on boot
+    orphan_policy: OrphanPolicy,
+    /// Settings this manager was built from, inherited by named instances
+    config: LineraConfig,
 }
 
 impl LineraManager {
-    pub fn new() -> Self {
-        // Default paths - can be configured
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        
+    /// Manager of the default instance
+    pub fn new(config: &LineraConfig) -> Self {
+        Self::from_config(
+            config,
+            Arc::new(ProcessCli::new(config.bin.display().to_string())),
+        )
+    }
+
+    /// Manager of the instance described by `config`, running linera through `cli`
+    pub fn from_config(config: &LineraConfig, cli: Arc<dyn LineraCli>) -> Self {
         Self {
-            linera_bin: std::env::var("LINERA_BIN")
-                .unwrap_or_else(|_| format!("{}/bin/linera", home)),
+            cli,
             service_process: Arc::new(RwLock::new(None)),
-            service_port: Arc::new(RwLock::new(None)),
-            wallet_path: std::env::var("LINERA_WALLET")
-                .unwrap_or_else(|_| format!("{}/linera-wallet.json", home)),
-            keystore_path: std::env::var("LINERA_KEYSTORE")
-                .unwrap_or_else(|_| format!("{}/linera-keystore.json", home)),
+            service_config: Arc::new(RwLock::new(None)),
+            wallet_path: config.wallet.display().to_string(),
+            keystore_path: config.keystore.display().to_string(),
+            logs: Arc::new(ServiceLogs::new(
+                config.log_buffer_lines,
+                config.service_log.clone(),
+                config.service_log_max_bytes,
+            )),
+            log_tasks: Arc::new(RwLock::new(Vec::new())),
+            supervision: Arc::new(RwLock::new(Supervision::default())),
+            supervisor: Arc::new(RwLock::new(None)),
+            stop_grace_period: Duration::from_secs(config.stop_grace_secs),
+            default_storage: config.storage.clone(),
+            state_file: config.state_file.clone(),
+            service_port: None,
+            orphan_policy: config.orphan_policy,
+            config: config.clone(),
         }
     }
 
+    /// Manager for a named instance with its own wallet and keystore.
+    ///
+    /// It runs the same linera binary with this manager's settings; the name is
+    /// appended to the state and log file paths so instances do not overwrite
+    /// each other's files.
+    pub fn for_instance(
+        &self,
+        name: &str,
+        wallet_path: String,
+        keystore_path: String,
+        default_storage: Option<String>,
+    ) -> Self {
+        let config = LineraConfig {
+            wallet: PathBuf::from(wallet_path),
+            keystore: PathBuf::from(keystore_path),
+            storage: default_storage.unwrap_or_else(|| self.config.storage.clone()),
+            state_file: instance_path(&self.config.state_file, name),
+            service_log: self
+                .config
+                .service_log
+                .as_deref()
+                .map(|path| instance_path(path, name)),
+            ..self.config.clone()
+        };
+        Self::from_config(&config, self.cli.clone())
+    }
+
+    /// The linera command line, shared with named instances
+    pub fn cli(&self) -> Arc<dyn LineraCli> {
+        self.cli.clone()
+    }
+
+    /// Run linera commands through `cli` instead, e.g. to meter them
+    pub fn with_cli(mut self, cli: Arc<dyn LineraCli>) -> Self {
+        self.cli = cli;
+        self
+    }
+
+    /// Start the service on `port` when a start request names none
+    pub fn with_service_port(mut self, port: Option<u16>) -> Self {
+        self.service_port = port;
+        self
+    }
+
+    /// Port the service starts on when a start request names none, if this instance has its own
+    pub fn service_port(&self) -> Option<u16> {
+        self.service_port
+    }
+
+    /// Path to the wallet file used by this instance
+    pub fn wallet_path(&self) -> &str {
+        &self.wallet_path
+    }
+
+    /// Path to the keystore file used by this instance
+    pub fn keystore_path(&self) -> &str {
+        &self.keystore_path
+    }
+
+    /// Captured output of the service
+    pub fn logs(&self) -> &Arc<ServiceLogs> {
+        &self.logs
+    }
+
     /// Check if the service is currently running
     pub async fn is_running(&self) -> bool {
         let process = self.service_process.read().await;
@@ -55,229 +255,962 @@ impl LineraManager {
 
     /// Get the current service port if running
     pub async fn get_port(&self) -> Option<u16> {
-        *self.service_port.read().await
+        self.service_config.read().await.as_ref().map(|c| c.port)
     }
 
     /// Initialize a new wallet using the faucet
     pub async fn init_wallet(&self, faucet_url: Option<&str>) -> Result<WalletInfo> {
-        let faucet = faucet_url.unwrap_or(DEFAULT_FAUCET_URL);
-        
+        if Path::new(&self.wallet_path).exists() {
+            return Err(ApiError::WalletAlreadyExists(self.wallet_path.clone()).into());
+        }
+        let faucet = reachable_faucet(faucet_url).await?;
+
         info!("Initializing wallet with faucet: {}", faucet);
 
         // Run: linera wallet init --faucet <url>
-        let output = Command::new(&self.linera_bin)
-            .args([
-                "--wallet", &self.wallet_path,
-                "--keystore", &self.keystore_path,
-                "wallet", "init",
-                "--faucet", faucet,
-            ])
-            .output()
+        let output = self
+            .cli
+            .run(&self.wallet_args(&["wallet", "init", "--faucet", faucet]))
             .await?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.success() {
+            let stderr = &output.stderr;
             error!("Failed to init wallet: {}", stderr);
-            return Err(anyhow!("Failed to initialize wallet: {}", stderr));
+            return Err(ApiError::CommandFailed(format!(
+                "Failed to initialize wallet: {}",
+                stderr
+            ))
+            .into());
         }
 
         // Get wallet info
         self.get_wallet_info().await
     }
 
+    /// Request another chain from the faucet and add it to the existing wallet
+    pub async fn request_chain(&self, req: &RequestChainRequest) -> Result<WalletChain> {
+        self.require_wallet()?;
+        let faucet = reachable_faucet(req.faucet_url.as_deref()).await?;
+
+        let mut args = vec!["wallet", "request-chain", "--faucet", faucet];
+        if req.set_default {
+            args.push("--set-default");
+        }
+        let output = self.cli.run(&self.wallet_args(&args)).await?;
+        if !output.success() {
+            return Err(ApiError::CommandFailed(format!(
+                "Failed to request chain: {}",
+                output.stderr
+            ))
+            .into());
+        }
+
+        let chain_id = crate::wallet::parse_requested_chain(&output.stdout)?;
+        info!("Requested chain {} from faucet {}", chain_id, faucet);
+        self.get_wallet_info()
+            .await?
+            .chains
+            .into_iter()
+            .find(|chain| chain.chain_id == chain_id)
+            .ok_or_else(|| anyhow!("Chain {} is missing from the wallet", chain_id))
+    }
+
+    /// Make `chain_id` the chain the wallet and the service default to
+    pub async fn set_default_chain(&self, chain_id: &str) -> Result<WalletInfo> {
+        self.wallet_with_chain(chain_id).await?;
+        self.wallet_chain_command("set-default", chain_id).await
+    }
+
+    /// Remove `chain_id` and its key from the wallet; the chain itself lives on
+    pub async fn forget_chain(&self, chain_id: &str) -> Result<WalletInfo> {
+        let wallet = self.wallet_with_chain(chain_id).await?;
+        // A wallet without chains can no longer be shown or used by the service
+        if wallet.chains.len() == 1 {
+            return Err(ApiError::InvalidRequest(format!(
+                "Chain {} is the only chain of the wallet",
+                chain_id
+            ))
+            .into());
+        }
+        self.wallet_chain_command("forget-chain", chain_id).await
+    }
+
+    /// The wallet, once `chain_id` is known to be one of its chains
+    async fn wallet_with_chain(&self, chain_id: &str) -> Result<WalletInfo> {
+        crate::wallet::validate_chain_id(chain_id)?;
+        let wallet = self.get_wallet_info().await?;
+        if wallet.chains.iter().any(|chain| chain.chain_id == chain_id) {
+            Ok(wallet)
+        } else {
+            Err(ApiError::ChainNotInWallet(chain_id.to_string()).into())
+        }
+    }
+
+    /// Run `linera wallet <command> <chain_id>` and return the wallet as it is afterwards
+    async fn wallet_chain_command(&self, command: &str, chain_id: &str) -> Result<WalletInfo> {
+        info!("Running wallet {} on chain {}", command, chain_id);
+        let output = self
+            .cli
+            .run(&self.wallet_args(&["wallet", command, chain_id]))
+            .await?;
+        if !output.success() {
+            error!("wallet {} failed: {}", command, output.stderr);
+            return Err(ApiError::CommandFailed(format!(
+                "Failed to run wallet {}: {}",
+                command, output.stderr
+            ))
+            .into());
+        }
+
+        self.get_wallet_info().await
+    }
+
     /// Get current wallet information
     pub async fn get_wallet_info(&self) -> Result<WalletInfo> {
+        self.require_wallet()?;
+
         // Run: linera wallet show
-        let output = Command::new(&self.linera_bin)
-            .args([
-                "--wallet", &self.wallet_path,
-                "--keystore", &self.keystore_path,
-                "wallet", "show",
-            ])
-            .output()
-            .await?;
+        let output = self.cli.run(&self.wallet_args(&["wallet", "show"])).await?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to get wallet info: {}", stderr));
+        if !output.success() {
+            let stderr = &output.stderr;
+            return Err(
+                ApiError::CommandFailed(format!("Failed to get wallet info: {}", stderr)).into(),
+            );
         }
 
         // Combine stdout and stderr since linera writes info to both
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let combined = format!("{}\n{}", stderr, stdout);
-        
-        // Parse the output to extract chain_id
-        let chain_id = self.extract_field_flexible(&combined, "Chain ID")?;
-        
-        // Public key may not exist for faucet-created wallets
-        let public_key = self.extract_field_flexible(&combined, "Default owner")
-            .unwrap_or_else(|_| "No owner key".to_string());
-
-        Ok(WalletInfo {
-            chain_id,
-            public_key,
-        })
+        let combined = format!("{}\n{}", output.stderr, output.stdout);
+
+        crate::wallet::parse_wallet_show(&combined)
     }
 
-    /// Start the linera service
-    pub async fn start_service(&self, port: u16) -> Result<()> {
-        // Check if already running
-        if self.is_running().await {
-            return Err(anyhow!("Service is already running"));
+    /// Snapshot of the service lifecycle for status reporting
+    pub async fn status(&self) -> ServiceStatus {
+        let supervision = self.supervision.read().await;
+
+        ServiceStatus {
+            running: self.is_running().await,
+            pid: self.get_pid().await,
+            port: self.get_port().await,
+            storage: self
+                .service_config
+                .read()
+                .await
+                .as_ref()
+                .map(|c| c.storage.clone()),
+            state: supervision.state,
+            restart_count: supervision.restart_count,
+            last_exit: supervision.last_exit.clone(),
+            uptime_secs: supervision
+                .started_at_ms
+                .map(|started| service_logs::now_ms().saturating_sub(started) / 1000),
         }
+    }
 
-        info!("Starting linera service on port {}", port);
+    /// Storage used when a start request does not name one
+    pub fn default_storage(&self) -> &str {
+        &self.default_storage
+    }
 
-        // Storage using memory (no persistence)
-        let storage = "memory";
+    /// Start the linera service, wait until its GraphQL endpoint answers and supervise it
+    pub async fn start_service(&self, config: ServiceConfig) -> Result<()> {
+        storage::validate(&config.storage)?;
+
+        let generation = {
+            let mut supervision = self.supervision.write().await;
+            if matches!(
+                supervision.state,
+                ServiceState::Starting | ServiceState::Running | ServiceState::Backoff
+            ) {
+                return Err(ApiError::ServiceAlreadyRunning.into());
+            }
+            supervision.state = ServiceState::Starting;
+            supervision.restart_count = 0;
+            supervision.generation
+        };
+
+        let launched = self.launch(&config).await;
+
+        // Held until the service is installed so a stop sees either nothing or all of it
+        let mut supervision = self.supervision.write().await;
+        if supervision.generation != generation {
+            drop(supervision);
+            if let Ok(mut child) = launched {
+                info!("Linera service was stopped while starting, killing it");
+                child.kill().await?;
+            }
+            return Err(ServiceStartFailure {
+                reason: "stopped while starting".to_string(),
+                exit_code: None,
+                stderr_tail: Vec::new(),
+            }
+            .into());
+        }
+
+        let child = match launched {
+            Ok(child) => child,
+            Err(e) => {
+                supervision.state = ServiceState::Crashed;
+                return Err(e);
+            }
+        };
+
+        self.save_record(child.id(), &config);
+        *self.service_process.write().await = Some(ServiceProcess::Child(child));
+        *self.service_config.write().await = Some(config.clone());
+        supervision.state = ServiceState::Running;
+        supervision.started_at_ms = Some(service_logs::now_ms());
+        drop(supervision);
+
+        info!("Linera service started on port {}", config.port);
+        let supervisor = tokio::spawn(self.clone().supervise(config));
+        *self.supervisor.write().await = Some(supervisor);
 
-        // Spawn: linera service --port <port>
-        let child = Command::new(&self.linera_bin)
-            .args([
-                "--wallet", &self.wallet_path,
-                "--keystore", &self.keystore_path,
-                "--storage", storage,
-                "service",
-                "--port", &port.to_string(),
-            ])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        *self.service_process.write().await = Some(child);
-        *self.service_port.write().await = Some(port);
-
-        // Give it a moment to start
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-        info!("Linera service started on port {}", port);
         Ok(())
     }
 
-    /// Stop the linera service
-    pub async fn stop_service(&self) -> Result<()> {
-        let mut process = self.service_process.write().await;
-        
-        if let Some(mut child) = process.take() {
-            info!("Stopping linera service");
-            child.kill().await?;
-            child.wait().await?;
-            *self.service_port.write().await = None;
-            info!("Linera service stopped");
-            Ok(())
-        } else {
-            Err(anyhow!("Service is not running"))
+    /// Deal with a service left running by a previous server instance, per the orphan policy
+    pub async fn reconcile(&self) {
+        let Some(record) = self.load_record() else {
+            return;
+        };
+
+        if !self.is_our_service(&record) {
+            info!("Service from previous run (pid {}) is gone", record.pid);
+            self.clear_record();
+            return;
+        }
+
+        match self.orphan_policy {
+            OrphanPolicy::Adopt => {
+                info!(
+                    "Adopting linera service left running on port {} (pid {})",
+                    record.config.port, record.pid
+                );
+                *self.service_process.write().await = Some(ServiceProcess::Adopted(record.pid));
+                *self.service_config.write().await = Some(record.config.clone());
+                self.set_running(record.started_at_ms).await;
+
+                let supervisor = tokio::spawn(self.clone().supervise(record.config));
+                *self.supervisor.write().await = Some(supervisor);
+            }
+            OrphanPolicy::Kill => {
+                info!("Stopping linera service left running (pid {})", record.pid);
+                let mut orphan = ServiceProcess::Adopted(record.pid);
+                if let Err(e) = orphan.terminate(self.stop_grace_period).await {
+                    error!("Failed to stop orphaned linera service: {}", e);
+                }
+                self.clear_record();
+            }
+        }
+    }
+
+    /// Whether the recorded pid is still alive and still the service we launched
+    fn is_our_service(&self, record: &ServiceRecord) -> bool {
+        if !service_process::is_alive(record.pid) {
+            return false;
+        }
+
+        // Pids get reused; only trust the record if the command line matches
+        service_process::cmdline(record.pid).is_some_and(|argv| argv.ends_with(&record.args))
+    }
+
+    fn save_record(&self, pid: Option<u32>, config: &ServiceConfig) {
+        let Some(pid) = pid else {
+            return;
+        };
+
+        let record = ServiceRecord {
+            pid,
+            args: self.service_args(config),
+            config: config.clone(),
+            started_at_ms: service_logs::now_ms(),
+        };
+
+        let result = serde_json::to_vec_pretty(&record)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(&self.state_file, json)?));
+        if let Err(e) = result {
+            warn!(
+                "Failed to write state file {}: {}",
+                self.state_file.display(),
+                e
+            );
+        }
+    }
+
+    fn load_record(&self) -> Option<ServiceRecord> {
+        let json = std::fs::read(&self.state_file).ok()?;
+        match serde_json::from_slice(&json) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                warn!(
+                    "Ignoring unreadable state file {}: {}",
+                    self.state_file.display(),
+                    e
+                );
+                self.clear_record();
+                None
+            }
+        }
+    }
+
+    /// Forget the recorded service, e.g. when the instance is removed
+    pub fn clear_record(&self) {
+        if Path::new(&self.state_file).exists() {
+            if let Err(e) = std::fs::remove_file(&self.state_file) {
+                warn!(
+                    "Failed to remove state file {}: {}",
+                    self.state_file.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Arguments passed to the linera binary to run the service
+    fn service_args(&self, config: &ServiceConfig) -> Vec<String> {
+        vec![
+            "--wallet".to_string(),
+            self.wallet_path.clone(),
+            "--keystore".to_string(),
+            self.keystore_path.clone(),
+            "--storage".to_string(),
+            config.storage.clone(),
+            "service".to_string(),
+            "--port".to_string(),
+            config.port.to_string(),
+        ]
+    }
+
+    /// Spawn the service process and poll it until it is ready, exits or times out
+    async fn launch(&self, config: &ServiceConfig) -> Result<Child> {
+        let port = config.port;
+        let startup_timeout = config.startup_timeout();
+
+        // Something else listening on the port would pass the readiness probe
+        if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            return Err(ApiError::PortInUse(port).into());
+        }
+
+        info!(
+            "Starting linera service on port {} with {} storage",
+            port, config.storage
+        );
+
+        // Spawn: linera service --port <port>
+        let mut child = self.cli.spawn_service(&self.service_args(config))?;
+
+        // Keep draining the pipes so a chatty service never blocks on a full pipe
+        let first_seq = self.logs.next_seq();
+        self.spawn_log_drains(&mut child).await;
+
+        // Poll the GraphQL endpoint until it responds, the child exits or we time out
+        let probe = GraphQLProxy::new(port);
+        let deadline = Instant::now() + startup_timeout;
+
+        loop {
+            if let Some(status) = child.try_wait()? {
+                let exit = service_process::exit_info(&status);
+                self.record_exit(exit.clone()).await;
+                let stderr_tail = self.stderr_tail(first_seq).await;
+                error!("Linera service exited during startup: {}", status);
+                return Err(ServiceStartFailure {
+                    reason: "process exited before becoming ready".to_string(),
+                    exit_code: exit.code,
+                    stderr_tail,
+                }
+                .into());
+            }
+
+            if probe.health_check().await {
+                return Ok(child);
+            }
+
+            if Instant::now() >= deadline {
+                warn!(
+                    "Linera service not ready after {:?}, killing it",
+                    startup_timeout
+                );
+                child.kill().await?;
+                let stderr_tail = self.stderr_tail(first_seq).await;
+                return Err(ServiceStartFailure {
+                    reason: format!("not ready after {} seconds", startup_timeout.as_secs()),
+                    exit_code: None,
+                    stderr_tail,
+                }
+                .into());
+            }
+
+            tokio::time::sleep(STARTUP_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Watch the running service and restart it according to `policy` when it exits
+    async fn supervise(self, config: ServiceConfig) {
+        let policy = &config.restart;
+        let mut attempts = 0u32;
+        let mut started = Instant::now();
+
+        loop {
+            let Some(exit) = self.wait_for_exit().await else {
+                return;
+            };
+            warn!("Linera service exited: {:?}", exit);
+            self.record_exit(exit.clone()).await;
+
+            // A service that stayed up for a while starts with a fresh backoff
+            if started.elapsed() >= STABLE_UPTIME {
+                attempts = 0;
+            }
+
+            let restart = match policy.mode {
+                RestartMode::Never => false,
+                RestartMode::OnFailure => !exit.success(),
+                RestartMode::Always => true,
+            };
+            if !restart {
+                let state = if exit.success() {
+                    ServiceState::Stopped
+                } else {
+                    ServiceState::Crashed
+                };
+                self.give_up(state).await;
+                return;
+            }
+
+            loop {
+                if policy.max_restarts.is_some_and(|max| attempts >= max) {
+                    error!(
+                        "Linera service restarted {} times in a row, giving up",
+                        attempts
+                    );
+                    self.give_up(ServiceState::Crashed).await;
+                    return;
+                }
+
+                let delay = backoff_delay(policy, attempts);
+                attempts += 1;
+                self.supervision.write().await.state = ServiceState::Backoff;
+                info!("Restarting linera service in {:?}", delay);
+                tokio::time::sleep(delay).await;
+
+                {
+                    let mut supervision = self.supervision.write().await;
+                    supervision.state = ServiceState::Starting;
+                    supervision.restart_count += 1;
+                }
+
+                match self.launch(&config).await {
+                    Ok(child) => {
+                        self.save_record(child.id(), &config);
+                        *self.service_process.write().await = Some(ServiceProcess::Child(child));
+                        self.set_running(service_logs::now_ms()).await;
+                        started = Instant::now();
+                        info!("Linera service restarted on port {}", config.port);
+                        break;
+                    }
+                    Err(e) => warn!("Failed to restart linera service: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Poll the child until it exits; `None` once it has been taken away by `stop_service`
+    async fn wait_for_exit(&self) -> Option<ExitInfo> {
+        loop {
+            tokio::time::sleep(SUPERVISOR_POLL_INTERVAL).await;
+
+            let mut process = self.service_process.write().await;
+            let running = process.as_mut()?;
+            match running.try_wait() {
+                Ok(Some(exit)) => {
+                    process.take();
+                    return Some(exit);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to poll linera service: {}", e),
+            }
+        }
+    }
+
+    async fn set_running(&self, started_at_ms: u64) {
+        let mut supervision = self.supervision.write().await;
+        supervision.state = ServiceState::Running;
+        supervision.started_at_ms = Some(started_at_ms);
+    }
+
+    async fn record_exit(&self, exit: ExitInfo) {
+        let mut supervision = self.supervision.write().await;
+        supervision.last_exit = Some(exit);
+        supervision.started_at_ms = None;
+    }
+
+    async fn give_up(&self, state: ServiceState) {
+        self.supervision.write().await.state = state;
+        *self.service_config.write().await = None;
+        self.clear_record();
+    }
+
+    /// Start background tasks that copy the child's stdout and stderr into the log buffer
+    async fn spawn_log_drains(&self, child: &mut Child) {
+        let mut tasks = self.log_tasks.write().await;
+        tasks.clear();
+
+        if let Some(stdout) = child.stdout.take() {
+            tasks.push(service_logs::spawn_drain(
+                stdout,
+                LogStream::Stdout,
+                self.logs.clone(),
+            ));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tasks.push(service_logs::spawn_drain(
+                stderr,
+                LogStream::Stderr,
+                self.logs.clone(),
+            ));
         }
     }
 
-    /// Add owners to a chain
-    pub async fn add_owner(&self, chain_id: &str, public_keys: Vec<String>) -> Result<OwnerAddResult> {
-        if public_keys.is_empty() {
-            return Err(anyhow!("At least one public key is required"));
+    /// Wait for the drains of a dead child to finish and return its last stderr lines
+    async fn stderr_tail(&self, since: u64) -> Vec<String> {
+        let tasks: Vec<_> = self.log_tasks.write().await.drain(..).collect();
+        for task in tasks {
+            let _ = tokio::time::timeout(LOG_DRAIN_TIMEOUT, task).await;
+        }
+
+        self.logs
+            .tail(STDERR_TAIL_LINES, Some(LogStream::Stderr), Some(since))
+            .into_iter()
+            .map(|l| l.line)
+            .collect()
+    }
+
+    /// Stop the linera service and its supervisor, escalating from SIGTERM to SIGKILL
+    pub async fn stop_service(&self, grace_period: Option<Duration>) -> Result<StopOutcome> {
+        let grace_period = grace_period.unwrap_or(self.stop_grace_period);
+
+        // Stop the supervisor first so it does not restart what we terminate
+        if let Some(supervisor) = self.supervisor.write().await.take() {
+            supervisor.abort();
+            let _ = supervisor.await;
+        }
+
+        let previous = {
+            let mut supervision = self.supervision.write().await;
+            supervision.generation += 1;
+            std::mem::replace(&mut supervision.state, ServiceState::Stopped)
+        };
+        *self.service_config.write().await = None;
+        self.clear_record();
+
+        let process = self.service_process.write().await.take();
+        match process {
+            Some(mut process) => {
+                info!("Stopping linera service");
+                let (signal, exit) = process.terminate(grace_period).await?;
+                self.record_exit(exit.clone()).await;
+                info!("Linera service stopped ({:?})", signal);
+                Ok(StopOutcome {
+                    signal: Some(signal),
+                    exit: Some(exit),
+                })
+            }
+            // Waiting to be restarted, or aborted mid-launch, counts as running
+            None if matches!(previous, ServiceState::Backoff | ServiceState::Starting) => {
+                Ok(StopOutcome {
+                    signal: None,
+                    exit: None,
+                })
+            }
+            None => Err(ApiError::ServiceNotRunning.into()),
         }
+    }
+
+    /// Replace the ownership of a chain with `ownership`.
+    ///
+    /// `change-ownership` sets the complete owner set, so `ownership` must
+    /// include every owner that is to remain.
+    pub async fn change_ownership(&self, ownership: &ChainOwnership) -> Result<()> {
+        crate::wallet::validate_chain_id(&ownership.chain_id)?;
+        self.require_wallet()?;
 
-        info!("Adding {} owners to chain {}", public_keys.len(), chain_id);
+        let weights: Vec<String> = ownership
+            .owners
+            .iter()
+            .map(|o| o.weight.to_string())
+            .collect();
+        let rounds = ownership.multi_leader_rounds.to_string();
+        let timeouts = &ownership.timeout_config;
+        let millis = |value: Option<u64>| value.map(|ms| ms.to_string());
+        let fast_round = millis(timeouts.fast_round_ms);
+        let base_timeout = millis(timeouts.base_timeout_ms);
+        let timeout_increment = millis(timeouts.timeout_increment_ms);
+        let fallback_duration = millis(timeouts.fallback_duration_ms);
 
-        // Build args: linera change-ownership --chain-id <id> --owner-public-keys <key1> <key2> ...
+        // linera change-ownership --chain-id <id> --super-owners <owner> ... --owners <owner> ...
         let mut args = vec![
-            "--wallet".to_string(), self.wallet_path.clone(),
-            "--keystore".to_string(), self.keystore_path.clone(),
-            "change-ownership".to_string(),
-            "--chain-id".to_string(), chain_id.to_string(),
+            "change-ownership",
+            "--chain-id",
+            ownership.chain_id.as_str(),
         ];
-        
-        for key in &public_keys {
-            args.push("--owner-public-keys".to_string());
-            args.push(key.clone());
+        for owner in &ownership.super_owners {
+            args.extend(["--super-owners", owner.as_str()]);
+        }
+        for owner in &ownership.owners {
+            args.extend(["--owners", owner.owner.as_str()]);
+        }
+        for weight in &weights {
+            args.extend(["--owner-weights", weight.as_str()]);
+        }
+        args.extend(["--multi-leader-rounds", rounds.as_str()]);
+        if ownership.open_multi_leader_rounds {
+            args.push("--open-multi-leader-rounds");
+        }
+        // Left out, the CLI would reset the chain's timeouts to its defaults
+        push_option(&mut args, "--fast-round-ms", fast_round.as_deref());
+        push_option(&mut args, "--base-timeout-ms", base_timeout.as_deref());
+        push_option(
+            &mut args,
+            "--timeout-increment-ms",
+            timeout_increment.as_deref(),
+        );
+        push_option(
+            &mut args,
+            "--fallback-duration-ms",
+            fallback_duration.as_deref(),
+        );
+
+        info!(
+            "Changing ownership of chain {} to {} super owners and {} owners",
+            ownership.chain_id,
+            ownership.super_owners.len(),
+            ownership.owners.len()
+        );
+        let output = self.cli.run(&self.wallet_args(&args)).await?;
+
+        if !output.success() {
+            let stderr = &output.stderr;
+            error!("Failed to change ownership: {}", stderr);
+            return Err(
+                ApiError::CommandFailed(format!("Failed to change ownership: {}", stderr)).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Open a chain owned by a single key
+    pub async fn open_chain(&self, req: OpenChainRequest) -> Result<ChainOpened> {
+        if let Some(from) = &req.from {
+            crate::wallet::validate_chain_id(from)?;
+        }
+        if let Some(owner) = &req.owner {
+            accounts::validate_owner(owner)?;
+        }
+        if let Some(balance) = &req.initial_balance {
+            accounts::validate_amount(balance)?;
+        }
+        self.require_wallet()?;
+
+        // linera open-chain [--from <chain>] [--owner <owner>] [--initial-balance <amount>]
+        let mut args = vec!["open-chain"];
+        push_option(&mut args, "--from", req.from.as_deref());
+        push_option(&mut args, "--owner", req.owner.as_deref());
+        push_option(
+            &mut args,
+            "--initial-balance",
+            req.initial_balance.as_deref(),
+        );
+
+        self.run_open_chain(&args).await
+    }
+
+    /// Open a chain owned by several keys
+    pub async fn open_multi_owner_chain(
+        &self,
+        req: OpenMultiOwnerChainRequest,
+    ) -> Result<ChainOpened> {
+        if let Some(from) = &req.from {
+            crate::wallet::validate_chain_id(from)?;
+        }
+        if req.owners.is_empty() {
+            return Err(
+                ApiError::InvalidRequest("At least one owner is required".to_string()).into(),
+            );
+        }
+        for owner in &req.owners {
+            accounts::validate_owner(owner)?;
+        }
+        if let Some(weights) = &req.owner_weights {
+            if weights.len() != req.owners.len() {
+                return Err(ApiError::InvalidRequest(format!(
+                    "Got {} owner weights for {} owners",
+                    weights.len(),
+                    req.owners.len()
+                ))
+                .into());
+            }
+        }
+        if let Some(balance) = &req.initial_balance {
+            accounts::validate_amount(balance)?;
+        }
+        self.require_wallet()?;
+
+        let weights: Vec<String> = req
+            .owner_weights
+            .iter()
+            .flatten()
+            .map(u64::to_string)
+            .collect();
+        let rounds = req.multi_leader_rounds.map(|rounds| rounds.to_string());
+
+        // linera open-multi-owner-chain --owners <owner> ... [--owner-weights <weight> ...]
+        let mut args = vec!["open-multi-owner-chain"];
+        push_option(&mut args, "--from", req.from.as_deref());
+        for owner in &req.owners {
+            args.extend(["--owners", owner.as_str()]);
+        }
+        for weight in &weights {
+            args.extend(["--owner-weights", weight.as_str()]);
+        }
+        push_option(&mut args, "--multi-leader-rounds", rounds.as_deref());
+        push_option(
+            &mut args,
+            "--initial-balance",
+            req.initial_balance.as_deref(),
+        );
+
+        self.run_open_chain(&args).await
+    }
+
+    async fn run_open_chain(&self, args: &[&str]) -> Result<ChainOpened> {
+        info!("Opening a chain: linera {}", args.join(" "));
+        let output = self.cli.run(&self.wallet_args(args)).await?;
+
+        if !output.success() {
+            error!("Failed to open chain: {}", output.stderr);
+            return Err(ApiError::CommandFailed(format!(
+                "Failed to open chain: {}",
+                output.stderr
+            ))
+            .into());
         }
 
-        let output = Command::new(&self.linera_bin)
-            .args(&args)
-            .output()
+        let opened = crate::wallet::parse_open_chain(&output.stdout)?;
+        info!("Opened chain {}", opened.chain_id);
+        Ok(opened)
+    }
+
+    /// Close a chain so it accepts no more blocks
+    pub async fn close_chain(&self, chain_id: &str) -> Result<ChainClosed> {
+        crate::wallet::validate_chain_id(chain_id)?;
+        self.require_wallet()?;
+
+        info!("Closing chain {}", chain_id);
+        let output = self
+            .cli
+            .run(&self.wallet_args(&["close-chain", chain_id]))
             .await?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            error!("Failed to add owner: {}", stderr);
-            return Err(anyhow!("Failed to add owner: {}", stderr));
+        if !output.success() {
+            error!("Failed to close chain: {}", output.stderr);
+            return Err(ApiError::CommandFailed(format!(
+                "Failed to close chain: {}",
+                output.stderr
+            ))
+            .into());
         }
 
-        Ok(OwnerAddResult {
-            success: true,
+        Ok(ChainClosed {
             chain_id: chain_id.to_string(),
-            owners: public_keys,
         })
     }
 
-    /// Generate a new keypair and return the public key
-    pub async fn keygen(&self) -> Result<String> {
-        let output = Command::new(&self.linera_bin)
-            .args([
-                "--wallet", &self.wallet_path,
-                "--keystore", &self.keystore_path,
-                "keygen",
-            ])
-            .output()
+    /// Balance of `chain_id` and of `owners` on it; without owners, of the
+    /// owner the wallet holds a key for on that chain, if any
+    pub async fn chain_balance(
+        &self,
+        chain_id: &str,
+        owners: Option<Vec<String>>,
+    ) -> Result<ChainBalance> {
+        crate::wallet::validate_chain_id(chain_id)?;
+        for owner in owners.iter().flatten() {
+            accounts::validate_owner(owner)?;
+        }
+        self.require_wallet()?;
+
+        let owners = match owners {
+            Some(owners) => owners,
+            None => self
+                .get_wallet_info()
+                .await?
+                .chains
+                .into_iter()
+                .find(|chain| chain.chain_id == chain_id)
+                .and_then(|chain| chain.owner)
+                .into_iter()
+                .collect(),
+        };
+
+        let mut account = Account {
+            chain_id: chain_id.to_string(),
+            owner: None,
+        };
+        let balance = self.query_balance(&account).await?;
+
+        let mut balances = Vec::new();
+        for owner in owners {
+            account.owner = Some(owner.clone());
+            let balance = self.query_balance(&account).await?;
+            balances.push(OwnerBalance { owner, balance });
+        }
+
+        Ok(ChainBalance {
+            chain_id: chain_id.to_string(),
+            balance,
+            owners: balances,
+        })
+    }
+
+    async fn query_balance(&self, account: &Account) -> Result<String> {
+        let account = account.to_string();
+        let output = self
+            .cli
+            .run(&self.wallet_args(&["query-balance", &account]))
             .await?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to generate keypair: {}", stderr));
+        if !output.success() {
+            return Err(ApiError::CommandFailed(format!(
+                "Failed to query balance of {}: {}",
+                account, output.stderr
+            ))
+            .into());
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        // The public key is usually printed directly
-        Ok(stdout.trim().to_string())
+        accounts::parse_balance(&output.stdout)
     }
 
-    /// Helper to extract field from linera output
-    fn extract_field(&self, output: &str, field: &str) -> Result<String> {
-        for line in output.lines() {
-            if line.contains(field) {
-                // Try to extract the value after the colon or equals
-                if let Some(value) = line.split(':').nth(1) {
-                    return Ok(value.trim().to_string());
-                }
-                if let Some(value) = line.split('=').nth(1) {
-                    return Ok(value.trim().to_string());
-                }
-            }
+    /// Transfer `amount` between accounts with the CLI
+    pub async fn transfer(&self, req: TransferRequest) -> Result<TransferResult> {
+        accounts::validate_account(&req.from)?;
+        accounts::validate_account(&req.to)?;
+        accounts::validate_amount(&req.amount)?;
+        self.require_wallet()?;
+
+        info!(
+            "Transferring {} from {} to {}",
+            req.amount, req.from, req.to
+        );
+
+        // linera transfer <amount> --from <account> --to <account>
+        let (from, to) = (req.from.to_string(), req.to.to_string());
+        let args = [
+            "transfer",
+            req.amount.as_str(),
+            "--from",
+            &from,
+            "--to",
+            &to,
+        ];
+        let output = self.cli.run(&self.wallet_args(&args)).await?;
+
+        if !output.success() {
+            error!("Transfer failed: {}", output.stderr);
+            return Err(
+                ApiError::CommandFailed(format!("Failed to transfer: {}", output.stderr)).into(),
+            );
         }
-        
-        // If not found with label, try to find a hex string that looks like a chain ID or key
-        for line in output.lines() {
-            let trimmed = line.trim();
-            if trimmed.len() >= 64 && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
-                return Ok(trimmed.to_string());
-            }
+
+        // The CLI does not print the certificate
+        Ok(TransferResult {
+            from: req.from,
+            to: req.to,
+            amount: req.amount,
+            certificate_hash: None,
+        })
+    }
+
+    /// Generate a new keypair and return the public key
+    pub async fn keygen(&self) -> Result<String> {
+        let output = self.cli.run(&self.wallet_args(&["keygen"])).await?;
+
+        if !output.success() {
+            let stderr = &output.stderr;
+            return Err(
+                ApiError::CommandFailed(format!("Failed to generate keypair: {}", stderr)).into(),
+            );
         }
-        
-        Err(anyhow!("Could not find {} in output", field))
+
+        // The public key is usually printed directly
+        Ok(output.stdout.trim().to_string())
     }
 
-    /// Helper to extract field from linera output (flexible with multiple spaces)
-    fn extract_field_flexible(&self, output: &str, field: &str) -> Result<String> {
-        for line in output.lines() {
-            if line.contains(field) {
-                // Split by field name and take what comes after
-                if let Some(rest) = line.split(field).nth(1) {
-                    // Remove leading colons, spaces, etc.
-                    let value = rest.trim_start_matches(':').trim();
-                    if !value.is_empty() {
-                        return Ok(value.to_string());
-                    }
-                }
-            }
+    /// Most commands need a wallet created by `init_wallet` first
+    fn require_wallet(&self) -> Result<()> {
+        if Path::new(&self.wallet_path).exists() {
+            Ok(())
+        } else {
+            Err(ApiError::WalletNotInitialized(self.wallet_path.clone()).into())
         }
-        
-        // Fallback to original method
-        self.extract_field(output, field)
+    }
+
+    /// `args` prefixed with this instance's wallet and keystore
+    fn wallet_args(&self, args: &[&str]) -> Vec<String> {
+        [
+            "--wallet",
+            &self.wallet_path,
+            "--keystore",
+            &self.keystore_path,
+        ]
+        .iter()
+        .chain(args)
+        .map(|arg| arg.to_string())
+        .collect()
     }
 }
 
-impl Default for LineraManager {
-    fn default() -> Self {
-        Self::new()
+/// `url`, or the testnet faucet, once it answers HTTP requests.
+///
+/// Checked up front so a wrong URL fails fast instead of after the CLI's own retries.
+async fn reachable_faucet(url: Option<&str>) -> Result<&str> {
+    let url = url.unwrap_or(DEFAULT_FAUCET_URL);
+    let is_http = url.starts_with("http://") || url.starts_with("https://");
+    if !is_http || url.contains(char::is_whitespace) {
+        return Err(ApiError::InvalidRequest(format!(
+            "Invalid faucet URL {:?}: expected an http:// or https:// URL",
+            url
+        ))
+        .into());
     }
+
+    reqwest::Client::new()
+        .get(url)
+        .timeout(FAUCET_CHECK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| ApiError::FaucetUnreachable(format!("{}: {}", url, e)))?;
+    Ok(url)
+}
+
+/// `path` with `.<name>` appended, so instances do not share files
+pub fn instance_path(path: &Path, name: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(format!(".{}", name));
+    PathBuf::from(path)
+}
+
+/// Append `--flag value` to `args` when `value` is set
+fn push_option<'a>(args: &mut Vec<&'a str>, flag: &'a str, value: Option<&'a str>) {
+    if let Some(value) = value {
+        args.extend([flag, value]);
+    }
+}
+
+/// Exponential backoff before restart attempt `attempt` (0-based)
+fn backoff_delay(policy: &RestartPolicy, attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+    let delay = policy.initial_backoff_ms.saturating_mul(factor);
+    Duration::from_millis(delay.min(policy.max_backoff_ms))
 }
@@ -1,269 +1,1097 @@
+mod accounts;
+mod auth;
+mod config;
+mod error;
 mod graphql_proxy;
+mod instances;
+mod linera_cli;
 mod linera_manager;
-mod models;
+mod metrics;
+mod openapi;
+mod ownership;
+mod service_logs;
+mod service_process;
+mod storage;
+mod wallet;
+
+// API types live in the library so clients can share them
+use linera_rest_server::models;
+
+#[cfg(test)]
+mod testing;
+#[cfg(test)]
+mod tests;
 
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, Request, State,
+    },
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use anyhow::Context;
+use axum_server::tls_rustls::RustlsConfig;
+use clap::{Parser, Subcommand};
+use std::convert::Infallible;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tower_http::cors::{Any, CorsLayer};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, WatchStream};
+use tokio_stream::{Stream, StreamExt};
 use tower_http::services::ServeDir;
-use tracing::{info, Level};
+use tracing::{error, info, Level};
 use tracing_subscriber::FmtSubscriber;
 
+use crate::auth::{ApiToken, Auth, Scope};
+use crate::config::{Config, ConfigArgs, TlsConfig};
+use crate::error::ApiError;
 use crate::graphql_proxy::GraphQLProxy;
-use crate::linera_manager::LineraManager;
+use crate::instances::{Instance, InstanceRegistry, SelectedInstance};
+use crate::linera_cli::{LineraCli, ProcessCli};
+use crate::linera_manager::{LineraManager, ServiceConfig};
+use crate::metrics::{MeteredCli, Metrics};
 use crate::models::*;
+use crate::openapi::{ErrorResponse, SystemGraphQLRequest};
 
 /// Application state shared across handlers
 pub struct AppState {
-    pub manager: LineraManager,
-    pub graphql_proxy: RwLock<Option<GraphQLProxy>>,
+    /// Tokens accepted by the API
+    pub auth: Auth,
+    /// The default instance plus any named ones
+    pub instances: InstanceRegistry,
+    /// Port of `linera service` when a start request does not name one
+    pub default_service_port: u16,
+    /// Flipped to `true` when the server is shutting down
+    pub shutdown: watch::Sender<bool>,
+    /// Request, GraphQL and linera command metrics
+    pub metrics: Arc<Metrics>,
 }
 
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(config: &Config) -> Self {
         Self {
-            manager: LineraManager::new(),
-            graphql_proxy: RwLock::new(None),
+            default_service_port: config.linera.service_port,
+            ..Self::with_default_instance(
+                Instance::new(
+                    instances::DEFAULT_INSTANCE,
+                    LineraManager::new(&config.linera)
+                        .with_service_port(Some(config.linera.service_port)),
+                ),
+                Auth::load(&config.tokens_file),
+                config.instances_file.clone(),
+            )
         }
     }
+
+    pub fn with_default_instance(mut default: Instance, auth: Auth, instances_file: PathBuf) -> Self {
+        // Named instances share the default instance's CLI, so they are metered too
+        let metrics = Arc::new(Metrics::new());
+        let cli = MeteredCli::new(default.manager.cli(), metrics.clone());
+        default.manager = default.manager.with_cli(Arc::new(cli));
+
+        Self {
+            auth,
+            instances: InstanceRegistry::new(Arc::new(default), instances_file),
+            default_service_port: config::DEFAULT_SERVICE_PORT,
+            shutdown: watch::channel(false).0,
+            metrics,
+        }
+    }
+}
+
+/// REST API server for managing a linera service
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Most verbose log level shown (trace, debug, info, warn, error)
+    #[arg(long, global = true, env = "LOG_LEVEL", default_value = "info")]
+    log_level: Level,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the REST server (the default)
+    Serve,
+    /// Validate the configuration and the linera binary, then exit
+    Check,
+    /// Print the effective configuration as TOML
+    PrintConfig,
+    /// Generate a random API token
+    GenToken {
+        /// Name shown in logs and 403 errors
+        #[arg(long, default_value = "admin")]
+        name: String,
+        #[arg(long, value_enum, default_value_t = Scope::Admin)]
+        scope: Scope,
+        /// Add the token to the tokens file instead of only printing it
+        #[arg(long)]
+        save: bool,
+    },
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
     // Initialize logging
     let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
+        .with_max_level(cli.log_level)
+        .with_writer(std::io::stderr)
         .finish();
     tracing::subscriber::set_global_default(subscriber).expect("Failed to set subscriber");
 
+    let config = Config::load(cli.config)?;
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config).await,
+        Command::Check => check(&config).await,
+        Command::PrintConfig => {
+            print!("{}", toml::to_string_pretty(&config)?);
+            Ok(())
+        }
+        Command::GenToken { name, scope, save } => {
+            let token = ApiToken {
+                name,
+                token: auth::random_token()?,
+                scope,
+            };
+            if save {
+                auth::save_token(&config.tokens_file, &token)?;
+                eprintln!("Saved token {:?} to {}", token.name, config.tokens_file.display());
+            }
+            println!("{}", token.token);
+            Ok(())
+        }
+    }
+}
+
+async fn serve(config: Config) -> anyhow::Result<()> {
     info!("Starting Linera REST API Server");
 
     // Create shared state
-    let state = Arc::new(AppState::new());
-
-    // Configure CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    // Static file serving for web UI
-    let web_dir = std::env::var("WEB_DIR").unwrap_or_else(|_| "./web".to_string());
-    
-    // Build router
-    let app = Router::new()
+    let state = Arc::new(AppState::new(&config));
+
+    // Load named instances and pick up services left running by a previous run
+    state.instances.load().await;
+
+    let app = app(state.clone(), &config);
+    let addr = config.addr();
+
+    let Some(tls) = &config.tls else {
+        info!("Listening on http://{}", addr);
+        info!("Web UI available at http://localhost:{}/", config.port);
+
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind {}", addr))?;
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown_signal(state))
+            .await?;
+        return Ok(());
+    };
+
+    let rustls_config = load_tls(tls).await?;
+
+    info!("Listening on https://{}", addr);
+    info!("Web UI available at https://localhost:{}/", config.port);
+
+    let handle = axum_server::Handle::new();
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            shutdown_signal(state).await;
+            handle.graceful_shutdown(None);
+        }
+    });
+    axum_server::bind_rustls(addr, rustls_config)
+        .handle(handle)
+        .serve(app.into_make_service())
+        .await
+        .with_context(|| format!("Failed to serve on {}", addr))?;
+    Ok(())
+}
+
+async fn load_tls(tls: &TlsConfig) -> anyhow::Result<RustlsConfig> {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    RustlsConfig::from_pem_file(&tls.cert, &tls.key)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to load TLS certificate {} and key {}",
+                tls.cert.display(),
+                tls.key.display()
+            )
+        })
+}
+
+/// Report whether the server could start and run linera with `config`
+async fn check(config: &Config) -> anyhow::Result<()> {
+    let mut failed = false;
+    let mut report = |ok: bool, what: String| {
+        println!("{} {}", if ok { "ok  " } else { "FAIL" }, what);
+        failed |= !ok;
+    };
+
+    report(true, format!("config (listening on {})", config.addr()));
+
+    let bin = config.linera.bin.display().to_string();
+    match ProcessCli::new(bin.clone()).run(&["--version".to_string()]).await {
+        Ok(output) if output.success() => {
+            let version = output.stdout.lines().next().unwrap_or_default().trim();
+            report(true, format!("linera binary {} ({})", bin, version));
+        }
+        Ok(output) => report(
+            false,
+            format!("linera binary {}: {}", bin, output.stderr.trim()),
+        ),
+        Err(e) => report(false, format!("linera binary: {}", e)),
+    }
+
+    if let Some(tls) = &config.tls {
+        match load_tls(tls).await {
+            Ok(_) => report(true, format!("TLS certificate {}", tls.cert.display())),
+            Err(e) => report(false, format!("{:#}", e)),
+        }
+    }
+
+    let wallet = &config.linera.wallet;
+    if wallet.exists() {
+        report(true, format!("wallet {}", wallet.display()));
+    } else {
+        println!("-    wallet {} not initialized yet", wallet.display());
+    }
+
+    let tokens = &config.tokens_file;
+    if tokens.exists() {
+        report(true, format!("tokens file {}", tokens.display()));
+    } else {
+        println!(
+            "-    tokens file {} missing; an admin token is generated on first start",
+            tokens.display()
+        );
+    }
+
+    if failed {
+        anyhow::bail!("check failed");
+    }
+    Ok(())
+}
+
+/// The full API plus the web UI, with the configured CORS policy.
+///
+/// The API lives under [`API_PREFIX`]; the unversioned paths of earlier releases
+/// still answer but are marked deprecated. Health check and API description stay
+/// available at both without being deprecated, for probes that cannot follow a move.
+fn app(state: Arc<AppState>, config: &Config) -> Router {
+    Router::new()
+        .nest(API_PREFIX, token_routes(&state).merge(public_routes()))
+        .merge(token_routes(&state).layer(middleware::from_fn(deprecated_alias)))
+        .merge(public_routes())
+        // Prometheus scrape target, outside the versioned API but behind a token
+        .route(
+            "/metrics",
+            get(metrics::metrics).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                auth::require_token,
+            )),
+        )
+        // Serve static files (web UI)
+        .fallback_service(ServeDir::new(&config.web_dir))
+        .layer(middleware::from_fn_with_state(state.clone(), metrics::track_requests))
+        .layer(config.cors_layer())
+        .with_state(state)
+}
+
+/// API routes that need a token, relative to the version prefix
+fn token_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        // Routes acting on the default instance
+        .merge(instance_routes())
+        // The same routes for a named instance
+        .nest("/instances/:name", instance_routes())
+        // Instance management
+        .route("/instances", get(list_instances).post(create_instance))
+        .route("/instances/:name", get(get_instance).delete(delete_instance))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::require_token))
+}
+
+/// API routes open to anyone, relative to the version prefix
+fn public_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // Health check
+        .route("/health", get(health_check))
+        // API description
+        .route("/openapi.json", get(openapi::openapi_json))
+}
+
+/// When the unversioned paths were deprecated, as an RFC 9745 date (2026-10-18)
+const UNVERSIONED_DEPRECATED_AT: &str = "@1792281600";
+
+/// Marks answers on an unversioned path as deprecated and points at its `/v1` successor
+async fn deprecated_alias(request: Request, next: Next) -> Response {
+    let successor = format!("<{}{}>; rel=\"successor-version\"", API_PREFIX, request.uri().path());
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static(UNVERSIONED_DEPRECATED_AT));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+/// Routes that act on a single instance, selected by [`SelectedInstance`]
+fn instance_routes() -> Router<Arc<AppState>> {
+    Router::new()
         // Service management
         .route("/service/start", post(start_service))
         .route("/service/stop", post(stop_service))
         .route("/service/status", get(get_status))
+        .route("/service/logs", get(get_logs))
+        .route("/service/logs/stream", get(stream_logs))
+        .route("/service/logs/ws", get(stream_logs_ws))
         // Wallet management
         .route("/wallet/init", post(init_wallet))
         .route("/wallet/info", get(get_wallet_info))
         .route("/wallet/keygen", post(keygen))
+        .route("/wallet/request-chain", post(request_chain))
+        .route("/wallet/default-chain", put(set_default_chain))
+        .route("/wallet/chains/:chain_id", delete(forget_chain))
         // Owner management
         .route("/owner/add", post(add_owner))
+        .route("/owner/remove", post(remove_owner))
+        // Chains and tokens
+        .route("/chains", get(list_chains))
+        .route("/chains/open", post(open_chain))
+        .route("/chains/open-multi-owner", post(open_multi_owner_chain))
+        .route("/chains/:chain_id/close", post(close_chain))
+        .route("/chains/:chain_id/balance", get(get_balance))
+        .route("/chains/:chain_id/owners", get(get_chain_owners))
+        .route("/transfer", post(transfer))
         // GraphQL proxy
         .route("/graphql", post(proxy_graphql))
         .route("/graphql/system", post(proxy_system_graphql))
-        // Health check
-        .route("/health", get(health_check))
-        // Serve static files (web UI)
-        .fallback_service(ServeDir::new(&web_dir))
-        .layer(cors)
-        .with_state(state);
+}
+
+/// Wait for Ctrl-C or SIGTERM, then stop the managed services and end live log streams
+async fn shutdown_signal(state: Arc<AppState>) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
 
-    // Start server
-    let port = std::env::var("PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(3000);
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-    let addr = format!("0.0.0.0:{}", port);
-    info!("Listening on {}", addr);
-    info!("Web UI available at http://localhost:{}/", port);
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutting down");
+    state.shutdown.send_replace(true);
 
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // A crashed service has no process left, so only these states have one to stop
+    for instance in state.instances.all().await {
+        if matches!(
+            instance.manager.status().await.state,
+            ServiceState::Starting | ServiceState::Running | ServiceState::Backoff
+        ) {
+            match instance.manager.stop_service(None).await {
+                Ok(outcome) => info!(
+                    "Linera service of {} stopped: {:?}",
+                    instance.name, outcome.signal
+                ),
+                Err(e) => error!("Failed to stop linera service of {}: {}", instance.name, e),
+            }
+        }
+    }
 }
 
 /// Health check endpoint
+#[utoipa::path(
+    get, path = "/health", tag = "health",
+    responses((status = 200, description = "Server is up", body = Object, example = json!({ "status": "ok" })))
+)]
 async fn health_check() -> impl IntoResponse {
     Json(serde_json::json!({ "status": "ok" }))
 }
 
+/// List the default and named instances
+#[utoipa::path(
+    get, path = "/instances", tag = "instances",
+    responses((status = 200, description = "Default instance first, then named ones", body = ApiResponse<Vec<InstanceInfo>>))
+)]
+async fn list_instances(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut infos = Vec::new();
+    for instance in state.instances.all().await {
+        infos.push(instance.info().await);
+    }
+
+    Json(ApiResponse::success(infos))
+}
+
+/// Register a named instance with its own wallet and keystore
+#[utoipa::path(
+    post, path = "/instances", tag = "instances",
+    request_body = CreateInstanceRequest,
+    responses(
+        (status = 201, body = ApiResponse<InstanceInfo>),
+        (status = 400, description = "Invalid name or storage", body = ErrorResponse),
+        (status = 409, description = "Name is taken", body = ErrorResponse),
+    )
+)]
+async fn create_instance(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateInstanceRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let instance = state.instances.create(req).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(instance.info().await)),
+    ))
+}
+
+/// Get a single instance
+#[utoipa::path(
+    get, path = "/instances/{name}", tag = "instances",
+    params(("name" = String, Path, description = "Instance name")),
+    responses(
+        (status = 200, body = ApiResponse<InstanceInfo>),
+        (status = 404, description = "Unknown instance", body = ErrorResponse),
+    )
+)]
+async fn get_instance(instance: SelectedInstance) -> impl IntoResponse {
+    Json(ApiResponse::success(instance.info().await))
+}
+
+/// Remove a stopped named instance (its wallet files are left in place)
+#[utoipa::path(
+    delete, path = "/instances/{name}", tag = "instances",
+    params(("name" = String, Path, description = "Instance name")),
+    responses(
+        (status = 200, body = ApiResponse<InstanceRemoved>),
+        (status = 400, description = "The default instance cannot be removed", body = ErrorResponse),
+        (status = 404, description = "Unknown instance", body = ErrorResponse),
+        (status = 409, description = "The instance's service is running", body = ErrorResponse),
+    )
+)]
+async fn delete_instance(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    state.instances.remove(&name).await?;
+
+    Ok(Json(ApiResponse::success(InstanceRemoved { name })))
+}
+
 /// Start the linera service
+#[utoipa::path(
+    post, path = "/service/start", tag = "service",
+    request_body = StartServiceRequest,
+    responses(
+        (status = 200, description = "The service answers GraphQL", body = ApiResponse<ServiceStarted>),
+        (status = 400, description = "Invalid storage", body = ErrorResponse),
+        (status = 409, description = "Already running or port in use", body = ErrorResponse),
+        (status = 500, description = "Did not become ready; `data` holds the exit code and stderr", body = ApiResponse<ServiceStartFailure>),
+        (status = 503, description = "linera binary missing", body = ErrorResponse),
+    )
+)]
 async fn start_service(
     State(state): State<Arc<AppState>>,
+    instance: SelectedInstance,
     Json(req): Json<StartServiceRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let port = req
+        .port
+        .or(instance.manager.service_port())
+        .unwrap_or(state.default_service_port);
+    let storage = req
+        .storage
+        .unwrap_or_else(|| instance.manager.default_storage().to_string());
+    let config = ServiceConfig {
+        port,
+        startup_timeout_secs: req.startup_timeout_secs,
+        restart: req.restart,
+        storage: storage.clone(),
+    };
+
+    instance.manager.start_service(config).await?;
+
+    // Initialize GraphQL proxy with the service port
+    *instance.graphql_proxy.write().await = Some(GraphQLProxy::new(port));
+
+    Ok(Json(ApiResponse::success(ServiceStarted { port, storage })))
+}
+
+/// Stop the linera service
+#[utoipa::path(
+    post, path = "/service/stop", tag = "service",
+    request_body(content = Option<StopServiceRequest>, description = "Optional"),
+    responses(
+        (status = 200, body = ApiResponse<StopOutcome>),
+        (status = 400, description = "Body is not a valid stop request", body = ErrorResponse),
+        (status = 503, description = "Service is not running", body = ErrorResponse),
+    )
+)]
+async fn stop_service(
+    instance: SelectedInstance,
+    body: Bytes,
+) -> Result<impl IntoResponse, ApiError> {
+    // No body means the defaults, but a body that does not parse is an error
+    let req: StopServiceRequest = if body.iter().all(u8::is_ascii_whitespace) {
+        StopServiceRequest::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| ApiError::InvalidRequest(format!("Invalid stop request: {}", e)))?
+    };
+    let grace_period = req.grace_period_secs.map(Duration::from_secs);
+
+    let outcome = instance.manager.stop_service(grace_period).await?;
+    *instance.graphql_proxy.write().await = None;
+
+    Ok(Json(ApiResponse::success(outcome)))
+}
+
+/// Get service status
+#[utoipa::path(
+    get, path = "/service/status", tag = "service",
+    responses((status = 200, body = ApiResponse<ServiceStatus>))
+)]
+async fn get_status(instance: SelectedInstance) -> impl IntoResponse {
+    Json(ApiResponse::success(instance.manager.status().await))
+}
+
+/// Get recent output of the linera service
+#[utoipa::path(
+    get, path = "/service/logs", tag = "service",
+    params(LogsQuery),
+    responses((status = 200, description = "Oldest line first", body = ApiResponse<Vec<LogLine>>))
+)]
+async fn get_logs(
+    instance: SelectedInstance,
+    Query(query): Query<LogsQuery>,
 ) -> impl IntoResponse {
-    let port = req.port;
-
-    match state.manager.start_service(port).await {
-        Ok(()) => {
-            // Initialize GraphQL proxy with the service port
-            *state.graphql_proxy.write().await = Some(GraphQLProxy::new(port));
-            
-            (
-                StatusCode::OK,
-                Json(ApiResponse::success(serde_json::json!({
-                    "status": "started",
-                    "port": port
-                }))),
-            )
+    let lines = instance.manager.logs().tail(query.lines, query.stream, None);
+
+    Json(ApiResponse::success(lines))
+}
+
+/// Follow the linera service output as Server-Sent Events
+#[utoipa::path(
+    get, path = "/service/logs/stream", tag = "service",
+    params(LogStreamQuery),
+    responses((status = 200, description = "`log` events carrying a LogLine, `lagged` events with the number of skipped lines", content_type = "text/event-stream", body = LogLine))
+)]
+async fn stream_logs(
+    State(state): State<Arc<AppState>>,
+    instance: SelectedInstance,
+    Query(query): Query<LogStreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let logs = instance.manager.logs();
+
+    // Subscribe before reading the backlog so no line falls in between
+    let live = BroadcastStream::new(logs.subscribe());
+    let backlog = logs.tail_matching(query.backlog, |l| query.matches(l));
+    let after = backlog.last().map(|l| l.seq);
+
+    let live = live.filter_map(move |item| match item {
+        Ok(line) if after.is_none_or(|seq| line.seq > seq) && query.matches(&line) => {
+            Some(log_event(&line))
         }
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<serde_json::Value>::error(e.to_string())),
-        ),
-    }
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            Some(Event::default().event("lagged").data(skipped.to_string()))
+        }
+    });
+
+    // Emits `None` once the server shuts down, which ends the stream
+    let shutdown = WatchStream::new(state.shutdown.subscribe())
+        .filter(|stopping| *stopping)
+        .map(|_| None);
+
+    let stream = tokio_stream::iter(backlog.iter().map(log_event).collect::<Vec<_>>())
+        .chain(live)
+        .map(Some)
+        .merge(shutdown)
+        .take_while(Option::is_some)
+        .filter_map(|event| event.map(Ok));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
-/// Stop the linera service
-async fn stop_service(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    match state.manager.stop_service().await {
-        Ok(()) => {
-            *state.graphql_proxy.write().await = None;
-            
-            (
-                StatusCode::OK,
-                Json(ApiResponse::success(serde_json::json!({
-                    "status": "stopped"
-                }))),
-            )
+fn log_event(line: &LogLine) -> Event {
+    Event::default()
+        .event("log")
+        .id(line.seq.to_string())
+        .json_data(line)
+        .unwrap_or_default()
+}
+
+/// Follow the linera service output over a WebSocket
+#[utoipa::path(
+    get, path = "/service/logs/ws", tag = "service",
+    params(LogStreamQuery),
+    responses((status = 101, description = "WebSocket sending one JSON LogLine per text message"))
+)]
+async fn stream_logs_ws(
+    State(state): State<Arc<AppState>>,
+    instance: SelectedInstance,
+    Query(query): Query<LogStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| forward_logs(socket, state, instance.0, query))
+}
+
+async fn forward_logs(
+    mut socket: WebSocket,
+    state: Arc<AppState>,
+    instance: Arc<Instance>,
+    query: LogStreamQuery,
+) {
+    let logs = instance.manager.logs();
+    let mut live = logs.subscribe();
+    let mut shutdown = state.shutdown.subscribe();
+    let backlog = logs.tail_matching(query.backlog, |l| query.matches(l));
+    let mut after = backlog.last().map(|l| l.seq);
+
+    for line in &backlog {
+        if send_log(&mut socket, line).await.is_err() {
+            return;
         }
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<serde_json::Value>::error(e.to_string())),
-        ),
     }
-}
 
-/// Get service status
-async fn get_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let running = state.manager.is_running().await;
-    let pid = state.manager.get_pid().await;
-    let port = state.manager.get_port().await;
+    loop {
+        tokio::select! {
+            received = live.recv() => match received {
+                Ok(line) => {
+                    if after.is_some_and(|seq| line.seq <= seq) || !query.matches(&line) {
+                        continue;
+                    }
+                    after = None;
+                    if send_log(&mut socket, &line).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    let notice = serde_json::json!({ "lagged": skipped }).to_string();
+                    if socket.send(Message::Text(notice)).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = async { shutdown.wait_for(|stopping| *stopping).await.map(|_| ()) } => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
 
-    Json(ApiResponse::success(ServiceStatus { running, pid, port }))
+async fn send_log(socket: &mut WebSocket, line: &LogLine) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(line).unwrap_or_default();
+    socket.send(Message::Text(text)).await
 }
 
 /// Initialize wallet with faucet
+#[utoipa::path(
+    post, path = "/wallet/init", tag = "wallet",
+    request_body = InitWalletRequest,
+    responses(
+        (status = 200, body = ApiResponse<WalletInfo>),
+        (status = 400, description = "Invalid faucet URL", body = ErrorResponse),
+        (status = 409, description = "Wallet already exists", body = ErrorResponse),
+        (status = 500, description = "linera wallet init failed", body = ErrorResponse),
+        (status = 502, description = "Faucet unreachable", body = ErrorResponse),
+    )
+)]
 async fn init_wallet(
-    State(state): State<Arc<AppState>>,
+    instance: SelectedInstance,
     Json(req): Json<InitWalletRequest>,
-) -> impl IntoResponse {
-    match state.manager.init_wallet(Some(&req.faucet_url)).await {
-        Ok(info) => (StatusCode::OK, Json(ApiResponse::success(info))),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<WalletInfo>::error(e.to_string())),
-        ),
-    }
+) -> Result<impl IntoResponse, ApiError> {
+    let info = instance.manager.init_wallet(Some(&req.faucet_url)).await?;
+
+    Ok(Json(ApiResponse::success(info)))
+}
+
+/// Request another chain from a faucet for the existing wallet
+#[utoipa::path(
+    post, path = "/wallet/request-chain", tag = "wallet",
+    request_body = RequestChainRequest,
+    responses(
+        (status = 200, description = "The new chain as the wallet lists it", body = ApiResponse<WalletChain>),
+        (status = 400, description = "Invalid faucet URL", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera wallet request-chain failed", body = ErrorResponse),
+        (status = 502, description = "Faucet unreachable", body = ErrorResponse),
+    )
+)]
+async fn request_chain(
+    instance: SelectedInstance,
+    Json(req): Json<RequestChainRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let chain = instance.manager.request_chain(&req).await?;
+
+    Ok(Json(ApiResponse::success(chain)))
+}
+
+/// Make a chain of the wallet its default chain
+#[utoipa::path(
+    put, path = "/wallet/default-chain", tag = "wallet",
+    request_body = SetDefaultChainRequest,
+    responses(
+        (status = 200, description = "The wallet with its new default chain", body = ApiResponse<WalletInfo>),
+        (status = 400, description = "Invalid chain ID", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized or chain not in it", body = ErrorResponse),
+        (status = 500, description = "linera wallet set-default failed", body = ErrorResponse),
+    )
+)]
+async fn set_default_chain(
+    instance: SelectedInstance,
+    Json(req): Json<SetDefaultChainRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let info = instance.manager.set_default_chain(&req.chain_id).await?;
+
+    Ok(Json(ApiResponse::success(info)))
+}
+
+/// Remove a chain from the wallet
+#[utoipa::path(
+    delete, path = "/wallet/chains/{chain_id}", tag = "wallet",
+    params(("chain_id" = String, Path, description = "Chain to forget")),
+    responses(
+        (status = 200, description = "The wallet without the chain", body = ApiResponse<WalletInfo>),
+        (status = 400, description = "Invalid chain ID, or the wallet's only chain", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized or chain not in it", body = ErrorResponse),
+        (status = 500, description = "linera wallet forget-chain failed", body = ErrorResponse),
+    )
+)]
+async fn forget_chain(
+    instance: SelectedInstance,
+    Path(path): Path<ChainPath>,
+) -> Result<impl IntoResponse, ApiError> {
+    let info = instance.manager.forget_chain(&path.chain_id).await?;
+
+    Ok(Json(ApiResponse::success(info)))
 }
 
 /// Get current wallet info
-async fn get_wallet_info(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    match state.manager.get_wallet_info().await {
-        Ok(info) => (StatusCode::OK, Json(ApiResponse::success(info))),
-        Err(e) => (
-            StatusCode::NOT_FOUND,
-            Json(ApiResponse::<WalletInfo>::error(e.to_string())),
-        ),
-    }
+#[utoipa::path(
+    get, path = "/wallet/info", tag = "wallet",
+    responses(
+        (status = 200, body = ApiResponse<WalletInfo>),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+    )
+)]
+async fn get_wallet_info(instance: SelectedInstance) -> Result<impl IntoResponse, ApiError> {
+    let info = instance.manager.get_wallet_info().await?;
+
+    Ok(Json(ApiResponse::success(info)))
 }
 
 /// Generate new keypair
-async fn keygen(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    match state.manager.keygen().await {
-        Ok(public_key) => (
-            StatusCode::OK,
-            Json(ApiResponse::success(serde_json::json!({
-                "public_key": public_key
-            }))),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<serde_json::Value>::error(e.to_string())),
-        ),
-    }
+#[utoipa::path(
+    post, path = "/wallet/keygen", tag = "wallet",
+    responses(
+        (status = 200, body = ApiResponse<Keygen>),
+        (status = 500, description = "linera keygen failed", body = ErrorResponse),
+    )
+)]
+async fn keygen(instance: SelectedInstance) -> Result<impl IntoResponse, ApiError> {
+    let public_key = instance.manager.keygen().await?;
+
+    Ok(Json(ApiResponse::success(Keygen { public_key })))
 }
 
-/// Add owner to chain
+/// Add owners to a chain, keeping its current owners
+///
+/// Reads the chain's ownership from the running service and submits it
+/// together with the new owners, since `change-ownership` replaces the owner set.
+#[utoipa::path(
+    post, path = "/owner/add", tag = "owner",
+    request_body = AddOwnerRequest,
+    responses(
+        (status = 200, body = ApiResponse<OwnerAddResult>),
+        (status = 400, description = "Invalid chain ID or owners", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera change-ownership failed", body = ErrorResponse),
+        (status = 502, description = "The service's GraphQL endpoint failed", body = ErrorResponse),
+        (status = 503, description = "Service is not running", body = ErrorResponse),
+    )
+)]
 async fn add_owner(
-    State(state): State<Arc<AppState>>,
+    instance: SelectedInstance,
     Json(req): Json<AddOwnerRequest>,
-) -> impl IntoResponse {
-    match state.manager.add_owner(&req.chain_id, req.public_keys).await {
-        Ok(result) => (StatusCode::OK, Json(ApiResponse::success(result))),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<OwnerAddResult>::error(e.to_string())),
-        ),
-    }
+) -> Result<impl IntoResponse, ApiError> {
+    let result = instance.add_owners(req).await?;
+
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// Remove owners from a chain, keeping the others
+#[utoipa::path(
+    post, path = "/owner/remove", tag = "owner",
+    request_body = RemoveOwnerRequest,
+    responses(
+        (status = 200, description = "Ownership of the chain afterwards", body = ApiResponse<ChainOwnership>),
+        (status = 400, description = "Invalid chain ID or owners, or no owner would remain", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera change-ownership failed", body = ErrorResponse),
+        (status = 502, description = "The service's GraphQL endpoint failed", body = ErrorResponse),
+        (status = 503, description = "Service is not running", body = ErrorResponse),
+    )
+)]
+async fn remove_owner(
+    instance: SelectedInstance,
+    Json(req): Json<RemoveOwnerRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let ownership = instance.remove_owners(req).await?;
+
+    Ok(Json(ApiResponse::success(ownership)))
+}
+
+/// Chain named in the path; a struct so the `:name` of instance routes is ignored
+#[derive(serde::Deserialize)]
+struct ChainPath {
+    chain_id: String,
+}
+
+/// Every chain the wallet tracks
+#[utoipa::path(
+    get, path = "/chains", tag = "chains",
+    responses(
+        (status = 200, body = ApiResponse<Vec<WalletChain>>),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+    )
+)]
+async fn list_chains(instance: SelectedInstance) -> Result<impl IntoResponse, ApiError> {
+    let wallet = instance.manager.get_wallet_info().await?;
+
+    Ok(Json(ApiResponse::success(wallet.chains)))
+}
+
+/// Open a chain owned by a single key
+#[utoipa::path(
+    post, path = "/chains/open", tag = "chains",
+    request_body = OpenChainRequest,
+    responses(
+        (status = 200, body = ApiResponse<ChainOpened>),
+        (status = 400, description = "Invalid chain ID, owner or balance", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera open-chain failed", body = ErrorResponse),
+    )
+)]
+async fn open_chain(
+    instance: SelectedInstance,
+    Json(req): Json<OpenChainRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let opened = instance.manager.open_chain(req).await?;
+
+    Ok(Json(ApiResponse::success(opened)))
+}
+
+/// Open a chain owned by several keys
+#[utoipa::path(
+    post, path = "/chains/open-multi-owner", tag = "chains",
+    request_body = OpenMultiOwnerChainRequest,
+    responses(
+        (status = 200, body = ApiResponse<ChainOpened>),
+        (status = 400, description = "Invalid owners, weights or balance", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera open-multi-owner-chain failed", body = ErrorResponse),
+    )
+)]
+async fn open_multi_owner_chain(
+    instance: SelectedInstance,
+    Json(req): Json<OpenMultiOwnerChainRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let opened = instance.manager.open_multi_owner_chain(req).await?;
+
+    Ok(Json(ApiResponse::success(opened)))
+}
+
+/// Close a chain for good
+#[utoipa::path(
+    post, path = "/chains/{chain_id}/close", tag = "chains",
+    params(("chain_id" = String, Path, description = "Chain to close")),
+    responses(
+        (status = 200, body = ApiResponse<ChainClosed>),
+        (status = 400, description = "Invalid chain ID", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera close-chain failed", body = ErrorResponse),
+    )
+)]
+async fn close_chain(
+    instance: SelectedInstance,
+    Path(path): Path<ChainPath>,
+) -> Result<impl IntoResponse, ApiError> {
+    let closed = instance.manager.close_chain(&path.chain_id).await?;
+
+    Ok(Json(ApiResponse::success(closed)))
+}
+
+/// Super owners, owners and their weights, as the chain records them
+#[utoipa::path(
+    get, path = "/chains/{chain_id}/owners", tag = "chains",
+    params(("chain_id" = String, Path, description = "Chain to inspect")),
+    responses(
+        (status = 200, body = ApiResponse<ChainOwnership>),
+        (status = 400, description = "Invalid chain ID", body = ErrorResponse),
+        (status = 502, description = "The service's GraphQL endpoint failed", body = ErrorResponse),
+        (status = 503, description = "Service is not running", body = ErrorResponse),
+    )
+)]
+async fn get_chain_owners(
+    instance: SelectedInstance,
+    Path(path): Path<ChainPath>,
+) -> Result<impl IntoResponse, ApiError> {
+    let ownership = instance.chain_ownership(&path.chain_id).await?;
+
+    Ok(Json(ApiResponse::success(ownership)))
+}
+
+/// Balance of a chain and of owners on it
+#[utoipa::path(
+    get, path = "/chains/{chain_id}/balance", tag = "chains",
+    params(("chain_id" = String, Path, description = "Chain to query"), BalanceQuery),
+    responses(
+        (status = 200, body = ApiResponse<ChainBalance>),
+        (status = 400, description = "Invalid chain ID or owner", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera query-balance failed", body = ErrorResponse),
+    )
+)]
+async fn get_balance(
+    instance: SelectedInstance,
+    Path(path): Path<ChainPath>,
+    Query(query): Query<BalanceQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let owners = query.owners.map(|owners| {
+        owners
+            .split(',')
+            .map(|owner| owner.trim().to_string())
+            .filter(|owner| !owner.is_empty())
+            .collect()
+    });
+    let balance = instance.manager.chain_balance(&path.chain_id, owners).await?;
+
+    Ok(Json(ApiResponse::success(balance)))
+}
+
+/// Transfer tokens between chain or owner accounts
+#[utoipa::path(
+    post, path = "/transfer", tag = "chains",
+    request_body = TransferRequest,
+    responses(
+        (status = 200, body = ApiResponse<TransferResult>),
+        (status = 400, description = "Invalid account or amount", body = ErrorResponse),
+        (status = 404, description = "Wallet not initialized", body = ErrorResponse),
+        (status = 500, description = "linera transfer failed", body = ErrorResponse),
+    )
+)]
+async fn transfer(
+    instance: SelectedInstance,
+    Json(req): Json<TransferRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let result = instance.transfer(req).await?;
+
+    Ok(Json(ApiResponse::success(result)))
 }
 
 /// Proxy GraphQL query to linera service
+#[utoipa::path(
+    post, path = "/graphql", tag = "graphql",
+    request_body = GraphQLProxyRequest,
+    responses(
+        (status = 200, description = "The service's GraphQL response, including any `errors`", body = Object),
+        (status = 400, description = "Invalid chain or application ID", body = ErrorResponse),
+        (status = 502, description = "The service's GraphQL endpoint failed", body = ErrorResponse),
+        (status = 503, description = "Service is not running", body = ErrorResponse),
+        (status = 504, description = "The service did not answer in time", body = ErrorResponse),
+    )
+)]
 async fn proxy_graphql(
     State(state): State<Arc<AppState>>,
+    instance: SelectedInstance,
     Json(req): Json<GraphQLProxyRequest>,
-) -> impl IntoResponse {
-    let proxy = state.graphql_proxy.read().await;
-    
-    match proxy.as_ref() {
-        Some(p) => {
-            match p.query(&req.chain_id, req.app_id.as_deref(), &req.query, req.variables).await {
-                Ok(result) => (StatusCode::OK, Json(result)),
-                Err(e) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": e.to_string()
-                    })),
-                ),
-            }
-        }
-        None => (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(serde_json::json!({
-                "error": "Linera service is not running"
-            })),
-        ),
+) -> Result<impl IntoResponse, ApiError> {
+    wallet::validate_chain_id(&req.chain_id)?;
+    // Also keeps the metrics' `app_id` label from growing without bound
+    if let Some(app_id) = &req.app_id {
+        wallet::validate_application_id(app_id)?;
     }
+
+    let proxy = instance.graphql_proxy.read().await;
+    let proxy = proxy.as_ref().ok_or(ApiError::ServiceNotRunning)?;
+    let started = std::time::Instant::now();
+    let result = proxy
+        .query(&req.chain_id, req.app_id.as_deref(), &req.query, req.variables)
+        .await
+        .map_err(ApiError::from);
+    state.metrics.observe_graphql(
+        Some(&req.chain_id),
+        req.app_id.as_deref(),
+        started.elapsed(),
+        &result,
+    );
+
+    Ok(Json(result?))
 }
 
 /// Proxy system GraphQL query
+#[utoipa::path(
+    post, path = "/graphql/system", tag = "graphql",
+    request_body = SystemGraphQLRequest,
+    responses(
+        (status = 200, description = "The service's GraphQL response, including any `errors`", body = Object),
+        (status = 502, description = "The service's GraphQL endpoint failed", body = ErrorResponse),
+        (status = 503, description = "Service is not running", body = ErrorResponse),
+        (status = 504, description = "The service did not answer in time", body = ErrorResponse),
+    )
+)]
 async fn proxy_system_graphql(
     State(state): State<Arc<AppState>>,
+    instance: SelectedInstance,
     Json(body): Json<serde_json::Value>,
-) -> impl IntoResponse {
-    let proxy = state.graphql_proxy.read().await;
-    
-    match proxy.as_ref() {
-        Some(p) => {
-            let query = body["query"].as_str().unwrap_or("");
-            let variables = body.get("variables").cloned();
-            
-            match p.system_query(query, variables).await {
-                Ok(result) => (StatusCode::OK, Json(result)),
-                Err(e) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": e.to_string()
-                    })),
-                ),
-            }
-        }
-        None => (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(serde_json::json!({
-                "error": "Linera service is not running"
-            })),
-        ),
-    }
+) -> Result<impl IntoResponse, ApiError> {
+    let proxy = instance.graphql_proxy.read().await;
+    let proxy = proxy.as_ref().ok_or(ApiError::ServiceNotRunning)?;
+
+    let query = body["query"].as_str().unwrap_or("");
+    let variables = body.get("variables").cloned();
+
+    let started = std::time::Instant::now();
+    let result = proxy.system_query(query, variables).await.map_err(ApiError::from);
+    state
+        .metrics
+        .observe_graphql(None, None, started.elapsed(), &result);
+
+    Ok(Json(result?))
 }
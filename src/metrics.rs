@@ -0,0 +1,322 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Child;
+
+use crate::error::ApiError;
+use crate::instances::InstanceRegistry;
+use crate::linera_cli::{CliOutput, LineraCli};
+use crate::AppState;
+
+/// Label of requests that matched no route, e.g. files of the web UI
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Chain label of queries to the service's system API
+const SYSTEM_CHAIN: &str = "system";
+
+/// Counters and histograms of the server, exported in Prometheus text format
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    graphql_duration: HistogramVec,
+    graphql_errors: IntCounterVec,
+    cli_invocations: IntCounterVec,
+    cli_duration: HistogramVec,
+    service_up: IntGaugeVec,
+    service_uptime: IntGaugeVec,
+    service_restarts: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time to answer HTTP requests",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let graphql_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "graphql_upstream_duration_seconds",
+                "Time the linera service took to answer proxied GraphQL queries",
+            ),
+            &["chain", "app"],
+        )
+        .unwrap();
+        let graphql_errors = IntCounterVec::new(
+            Opts::new(
+                "graphql_upstream_errors_total",
+                "Proxied GraphQL queries that failed or returned errors",
+            ),
+            &["chain", "app", "kind"],
+        )
+        .unwrap();
+        let cli_invocations = IntCounterVec::new(
+            Opts::new("linera_cli_invocations_total", "Runs of the linera binary"),
+            &["command", "outcome"],
+        )
+        .unwrap();
+        let cli_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "linera_cli_duration_seconds",
+                "Time linera commands took to complete",
+            )
+            .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0]),
+            &["command"],
+        )
+        .unwrap();
+        let service_up = IntGaugeVec::new(
+            Opts::new(
+                "linera_service_up",
+                "Whether the instance's linera service runs",
+            ),
+            &["instance"],
+        )
+        .unwrap();
+        let service_uptime = IntGaugeVec::new(
+            Opts::new(
+                "linera_service_uptime_seconds",
+                "Seconds since the running linera service process started",
+            ),
+            &["instance"],
+        )
+        .unwrap();
+        let service_restarts = IntGaugeVec::new(
+            Opts::new(
+                "linera_service_restarts",
+                "Automatic restarts since the service was last started through the API",
+            ),
+            &["instance"],
+        )
+        .unwrap();
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_duration.clone()),
+            Box::new(graphql_duration.clone()),
+            Box::new(graphql_errors.clone()),
+            Box::new(cli_invocations.clone()),
+            Box::new(cli_duration.clone()),
+            Box::new(service_up.clone()),
+            Box::new(service_uptime.clone()),
+            Box::new(service_restarts.clone()),
+        ] {
+            registry.register(collector).unwrap();
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            graphql_duration,
+            graphql_errors,
+            cli_invocations,
+            cli_duration,
+            service_up,
+            service_uptime,
+            service_restarts,
+        }
+    }
+
+    /// Record a proxied query; `chain_id` is `None` for the system API
+    pub fn observe_graphql(
+        &self,
+        chain_id: Option<&str>,
+        app_id: Option<&str>,
+        elapsed: Duration,
+        result: &Result<Value, ApiError>,
+    ) {
+        let chain = chain_id.unwrap_or(SYSTEM_CHAIN);
+        let app = app_id.unwrap_or("");
+        self.graphql_duration
+            .with_label_values(&[chain, app])
+            .observe(elapsed.as_secs_f64());
+
+        let kind = match result {
+            Ok(response) if response.get("errors").is_some_and(|e| !e.is_null()) => {
+                "response_errors".to_string()
+            }
+            Ok(_) => return,
+            Err(e) => e.code().to_string(),
+        };
+        self.graphql_errors
+            .with_label_values(&[chain, app, &kind])
+            .inc();
+    }
+
+    /// All metrics in Prometheus text format, with service gauges read from `instances`
+    pub async fn render(&self, instances: &InstanceRegistry) -> String {
+        // Removed instances must not linger
+        self.service_up.reset();
+        self.service_uptime.reset();
+        self.service_restarts.reset();
+        for instance in instances.all().await {
+            let status = instance.manager.status().await;
+            let name = [instance.name.as_str()];
+            self.service_up
+                .with_label_values(&name)
+                .set(status.running as i64);
+            self.service_uptime
+                .with_label_values(&name)
+                .set(status.uptime_secs.unwrap_or(0) as i64);
+            self.service_restarts
+                .with_label_values(&name)
+                .set(status.restart_count as i64);
+        }
+
+        let mut out = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut out)
+            .unwrap_or_default();
+        String::from_utf8(out).unwrap_or_default()
+    }
+}
+
+/// Count and time every request under the route it matched
+pub async fn track_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    let metrics = &state.metrics;
+    metrics
+        .http_requests
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .inc();
+    metrics
+        .http_duration
+        .with_label_values(&[&method, &route])
+        .observe(started.elapsed().as_secs_f64());
+    response
+}
+
+/// Prometheus scrape endpoint
+pub async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [("content-type", prometheus::TEXT_FORMAT)],
+        state.metrics.render(&state.instances).await,
+    )
+}
+
+/// A [`LineraCli`] that counts and times the commands it runs
+pub struct MeteredCli {
+    inner: Arc<dyn LineraCli>,
+    metrics: Arc<Metrics>,
+}
+
+impl MeteredCli {
+    pub fn new(inner: Arc<dyn LineraCli>, metrics: Arc<Metrics>) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+#[async_trait]
+impl LineraCli for MeteredCli {
+    async fn run(&self, args: &[String]) -> Result<CliOutput> {
+        let command = subcommand(args);
+        let started = Instant::now();
+        let result = self.inner.run(args).await;
+
+        let outcome = match &result {
+            Ok(output) if output.success() => "success",
+            Ok(_) => "failure",
+            Err(_) => "error",
+        };
+        self.metrics
+            .cli_invocations
+            .with_label_values(&[&command, outcome])
+            .inc();
+        self.metrics
+            .cli_duration
+            .with_label_values(&[&command])
+            .observe(started.elapsed().as_secs_f64());
+        result
+    }
+
+    fn spawn_service(&self, args: &[String]) -> Result<Child> {
+        let result = self.inner.spawn_service(args);
+        let outcome = if result.is_ok() { "success" } else { "error" };
+        self.metrics
+            .cli_invocations
+            .with_label_values(&[&subcommand(args), outcome])
+            .inc();
+        result
+    }
+}
+
+/// Subcommand of a linera command line, e.g. `wallet show`, without options or
+/// values that would make one series per chain
+fn subcommand(args: &[String]) -> String {
+    let mut words = args.iter().map(String::as_str);
+    let mut command = Vec::new();
+    while let Some(word) = words.next() {
+        if word.starts_with("--") {
+            if !command.is_empty() {
+                break;
+            }
+            // Global options come with a value
+            words.next();
+        } else if command.len() < 2 && word.chars().all(|c| c.is_ascii_lowercase() || c == '-') {
+            command.push(word);
+        } else {
+            break;
+        }
+    }
+    command.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &str) -> Vec<String> {
+        command.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn subcommand_skips_options_and_values() {
+        assert_eq!(
+            subcommand(&args("--wallet w.json --keystore k.json wallet show")),
+            "wallet show"
+        );
+        assert_eq!(
+            subcommand(&args("--wallet w --storage memory service --port 8080")),
+            "service"
+        );
+        assert_eq!(subcommand(&args("keygen")), "keygen");
+        assert_eq!(
+            subcommand(&args("--wallet w query-balance e476187f6ddfeb9d")),
+            "query-balance"
+        );
+    }
+}
@@ -1,33 +1,165 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Path prefix of the current API version; the types below are its contract
+pub const API_PREFIX: &str = "/v1";
 
 /// Request to start linera service
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct StartServiceRequest {
-    #[serde(default = "default_port")]
+    /// Defaults to the configured `linera.service_port`
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// How long to wait for the GraphQL endpoint to come up
+    #[serde(default = "default_startup_timeout_secs")]
+    pub startup_timeout_secs: u64,
+    /// What to do when the service exits on its own
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// `--storage` value: `memory`, `rocksdb:<path>` or any other linera storage URI
+    #[serde(default)]
+    pub storage: Option<String>,
+}
+
+impl Default for StartServiceRequest {
+    fn default() -> Self {
+        Self {
+            port: None,
+            startup_timeout_secs: default_startup_timeout_secs(),
+            restart: RestartPolicy::default(),
+            storage: None,
+        }
+    }
+}
+
+fn default_startup_timeout_secs() -> u64 {
+    30
+}
+
+/// Answer to a successful start request
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "status", rename = "started")]
+pub struct ServiceStarted {
     pub port: u16,
+    pub storage: String,
+}
+
+/// Request to stop linera service
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct StopServiceRequest {
+    /// Seconds to wait after SIGTERM before sending SIGKILL
+    #[serde(default)]
+    pub grace_period_secs: Option<u64>,
+}
+
+/// When the supervisor restarts a service that exited on its own
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
 }
 
-fn default_port() -> u16 {
-    8080
+/// Automatic restart settings for the linera service
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub mode: RestartMode,
+    /// Give up after this many consecutive restarts (unlimited if absent)
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::default(),
+            max_restarts: None,
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    60_000
+}
+
+/// Request to register a named instance
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateInstanceRequest {
+    pub name: String,
+    /// Defaults to `~/linera-wallet.<name>.json`
+    #[serde(default)]
+    pub wallet_path: Option<String>,
+    /// Defaults to `~/linera-keystore.<name>.json`
+    #[serde(default)]
+    pub keystore_path: Option<String>,
+    /// Storage used when a start request for this instance names none
+    #[serde(default)]
+    pub storage: Option<String>,
+    /// Port of this instance's service when a start request names none; must
+    /// differ from the other instances' ports [default: the server's service port]
+    #[serde(default)]
+    pub service_port: Option<u16>,
 }
 
 /// Request to initialize wallet
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct InitWalletRequest {
     pub faucet_url: String,
 }
 
+/// Request for another chain from a faucet, added to the existing wallet
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct RequestChainRequest {
+    /// Defaults to the testnet faucet
+    #[serde(default)]
+    pub faucet_url: Option<String>,
+    /// Make the new chain the wallet's default
+    #[serde(default)]
+    pub set_default: bool,
+}
+
+/// Request to change the wallet's default chain
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SetDefaultChainRequest {
+    pub chain_id: String,
+}
+
 /// Request to add owner to chain
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AddOwnerRequest {
     pub chain_id: String,
+    /// Owners to add next to the current ones, e.g. from `/wallet/keygen`
     pub public_keys: Vec<String>,
+    /// Weight of each new owner [default: 100]
+    #[serde(default)]
+    pub weight: Option<u64>,
+}
+
+/// Request to remove owners from a chain, keeping the others
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RemoveOwnerRequest {
+    pub chain_id: String,
+    pub owners: Vec<String>,
 }
 
 /// Request to proxy GraphQL query
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct GraphQLProxyRequest {
     pub chain_id: String,
+    /// Application to query, 64 hex characters; the chain itself if absent
     #[serde(default)]
     pub app_id: Option<String>,
     pub query: String,
@@ -35,22 +167,66 @@ pub struct GraphQLProxyRequest {
     pub variables: Option<serde_json::Value>,
 }
 
-/// Generic API response
-#[derive(Debug, Serialize)]
-pub struct ApiResponse<T: Serialize> {
+/// Query parameters for fetching service logs
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LogsQuery {
+    #[serde(default = "default_log_lines")]
+    pub lines: usize,
+    #[serde(default)]
+    pub stream: Option<LogStream>,
+}
+
+fn default_log_lines() -> usize {
+    100
+}
+
+/// Query parameters for live log streaming
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LogStreamQuery {
+    #[serde(default)]
+    pub stream: Option<LogStream>,
+    /// Minimum level; lines without a recognizable level are dropped when set
+    #[serde(default)]
+    pub level: Option<LogLevel>,
+    /// Number of buffered lines to replay before following
+    #[serde(default)]
+    pub backlog: usize,
+}
+
+impl LogStreamQuery {
+    pub fn matches(&self, line: &LogLine) -> bool {
+        self.stream.is_none_or(|s| line.stream == s)
+            && self
+                .level
+                .is_none_or(|min| line.level.is_some_and(|level| level >= min))
+    }
+}
+
+/// Envelope of every `/v1` answer: `data` on success, `error` and `code` on failure.
+///
+/// Fields are only ever added to it within a version; removing or changing one
+/// means a new prefix.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiResponse<T> {
     pub success: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Stable identifier of the error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
 }
 
-impl<T: Serialize> ApiResponse<T> {
+impl<T> ApiResponse<T> {
     pub fn success(data: T) -> Self {
         Self {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
         }
     }
 
@@ -59,29 +235,405 @@ impl<T: Serialize> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message.into()),
+            code: None,
         }
     }
+
+    /// Error response carrying structured details about the failure
+    pub fn failure(message: impl Into<String>, data: T) -> Self {
+        Self {
+            success: false,
+            data: Some(data),
+            error: Some(message.into()),
+            code: None,
+        }
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+}
+
+/// Machine-readable error identifier that stays the same when messages are reworded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidRequest,
+    InvalidChainId,
+    InvalidStorage,
+    InvalidOwner,
+    InvalidAmount,
+    WalletNotInitialized,
+    WalletAlreadyExists,
+    ChainNotInWallet,
+    Unauthorized,
+    Forbidden,
+    InstanceNotFound,
+    InstanceAlreadyExists,
+    InstanceRunning,
+    ServiceAlreadyRunning,
+    ServiceNotRunning,
+    PortInUse,
+    #[serde(rename = "linera_binary_missing")]
+    BinaryMissing,
+    ServiceStartFailed,
+    CommandFailed,
+    #[serde(rename = "graphql_error")]
+    GraphQL,
+    #[serde(rename = "graphql_timeout")]
+    GraphQLTimeout,
+    FaucetUnreachable,
+    #[serde(rename = "internal_error")]
+    Internal,
+    /// A code added to the server after this client was built
+    #[serde(other)]
+    Unknown,
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = serde_json::to_value(self).unwrap_or_default();
+        f.write_str(code.as_str().unwrap_or("unknown"))
+    }
+}
+
+/// Lifecycle state of the managed service
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceState {
+    Starting,
+    Running,
+    Crashed,
+    Backoff,
+    #[default]
+    Stopped,
+}
+
+/// How the service process last exited
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExitInfo {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub timestamp_ms: u64,
+}
+
+impl ExitInfo {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// Signal that ended the service during a stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ShutdownSignal {
+    Sigterm,
+    Sigkill,
+}
+
+/// How a stop request was carried out
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "status", rename = "stopped")]
+pub struct StopOutcome {
+    /// `None` when no process was alive (e.g. waiting to be restarted)
+    pub signal: Option<ShutdownSignal>,
+    pub exit: Option<ExitInfo>,
 }
 
 /// Service status response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ServiceStatus {
     pub running: bool,
     pub pid: Option<u32>,
     pub port: Option<u16>,
+    pub storage: Option<String>,
+    pub state: ServiceState,
+    pub restart_count: u32,
+    pub last_exit: Option<ExitInfo>,
+    /// Seconds since the current process started, while it runs
+    #[serde(default)]
+    pub uptime_secs: Option<u64>,
+}
+
+/// Details about a linera service that failed to become ready
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServiceStartFailure {
+    pub reason: String,
+    pub exit_code: Option<i32>,
+    pub stderr_tail: Vec<String>,
+}
+
+impl std::fmt::Display for ServiceStartFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Linera service failed to start: {}", self.reason)?;
+        if let Some(code) = self.exit_code {
+            write!(f, " (exit code {})", code)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ServiceStartFailure {}
+
+/// Answer to removing a named instance
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "status", rename = "removed")]
+pub struct InstanceRemoved {
+    pub name: String,
 }
 
-/// Wallet initialization response
-#[derive(Debug, Serialize)]
+/// A managed linera instance and the state of its service
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InstanceInfo {
+    pub name: String,
+    pub wallet_path: String,
+    pub keystore_path: String,
+    pub default_storage: String,
+    /// Port the service starts on by default, `None` for the server's service port
+    pub service_port: Option<u16>,
+    pub status: ServiceStatus,
+}
+
+/// Wallet contents as reported by `linera wallet show`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct WalletInfo {
+    /// Default chain, or the first chain if none is marked default
+    pub chain_id: String,
+    /// Owner of `chain_id`, or "No owner key" if the wallet holds none
+    pub public_key: String,
+    /// Chain marked default in the wallet, `None` if no chain is, e.g. after
+    /// forgetting the default chain
+    pub default_chain: Option<String>,
+    pub chains: Vec<WalletChain>,
+}
+
+/// A chain tracked by the wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WalletChain {
     pub chain_id: String,
+    /// Owner the wallet proposes blocks as, if it holds a key for this chain
+    pub owner: Option<String>,
+    /// Height of the next block, i.e. the number of blocks the wallet knows of
+    pub block_height: u64,
+    pub timestamp: Option<String>,
+    pub epoch: Option<u64>,
+    pub latest_block_hash: Option<String>,
+    pub is_default: bool,
+}
+
+/// A freshly generated keypair
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Keygen {
     pub public_key: String,
 }
 
 /// Owner addition response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct OwnerAddResult {
     pub success: bool,
     pub chain_id: String,
+    /// The owners that were added
+    pub owners: Vec<String>,
+    /// Ownership of the chain afterwards
+    pub ownership: ChainOwnership,
+}
+
+/// Who may propose blocks on a chain, as the chain records it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChainOwnership {
+    pub chain_id: String,
+    /// Owners that may propose in the fast round
+    pub super_owners: Vec<String>,
+    pub owners: Vec<OwnerWeight>,
+    /// Rounds in which every owner may propose a block
+    pub multi_leader_rounds: u32,
+    /// Whether anyone, not only owners, may propose in the multi-leader rounds
+    pub open_multi_leader_rounds: bool,
+    pub timeout_config: TimeoutConfig,
+}
+
+/// Round timeouts of a chain in milliseconds; `None` where the chain reported none
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TimeoutConfig {
+    /// Duration of the fast round, `None` if it has no limit
+    pub fast_round_ms: Option<u64>,
+    pub base_timeout_ms: Option<u64>,
+    /// Added to the timeout of each further round
+    pub timeout_increment_ms: Option<u64>,
+    /// Time after which the chain falls back to validators proposing blocks
+    pub fallback_duration_ms: Option<u64>,
+}
+
+/// A regular owner of a chain and its weight in leader election
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct OwnerWeight {
+    pub owner: String,
+    pub weight: u64,
+}
+
+/// A chain's own balance, or an owner's account on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Account {
+    pub chain_id: String,
+    /// `0x`-prefixed owner address; the chain balance itself when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+/// The linera CLI's account syntax: `owner@chain_id`, or `chain_id` alone
+impl std::fmt::Display for Account {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.owner {
+            Some(owner) => write!(f, "{}@{}", owner, self.chain_id),
+            None => f.write_str(&self.chain_id),
+        }
+    }
+}
+
+impl std::str::FromStr for Account {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once('@') {
+            Some((owner, chain_id)) => Account {
+                chain_id: chain_id.to_string(),
+                owner: Some(owner.to_string()),
+            },
+            None => Account {
+                chain_id: s.to_string(),
+                owner: None,
+            },
+        })
+    }
+}
+
+/// Request to move tokens between accounts
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TransferRequest {
+    /// Account to debit; the wallet must hold a key for its chain
+    pub from: Account,
+    pub to: Account,
+    /// Decimal amount with at most 18 decimals, e.g. `1.5`
+    pub amount: String,
+}
+
+/// Transfer response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TransferResult {
+    pub from: Account,
+    pub to: Account,
+    pub amount: String,
+    /// Hash of the certificate with the transfer, known when it went through the running
+    /// service; null when the service is stopped and the CLI made the transfer
+    pub certificate_hash: Option<String>,
+}
+
+/// Query parameters for a chain balance
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BalanceQuery {
+    /// Comma-separated owners to report; defaults to the wallet's owner of the chain
+    pub owners: Option<String>,
+}
+
+/// Balances of a chain and of owners on it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ChainBalance {
+    pub chain_id: String,
+    /// The chain's own balance
+    pub balance: String,
+    pub owners: Vec<OwnerBalance>,
+}
+
+/// Balance of one owner's account on a chain
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OwnerBalance {
+    pub owner: String,
+    pub balance: String,
+}
+
+/// Request to open a chain with a single owner
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct OpenChainRequest {
+    /// Chain that creates and funds the new one [default: the wallet's default chain]
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Owner of the new chain [default: a new key generated in the wallet]
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Tokens moved from `from` to the new chain
+    #[serde(default)]
+    pub initial_balance: Option<String>,
+}
+
+/// Request to open a chain with several owners
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct OpenMultiOwnerChainRequest {
+    /// Chain that creates and funds the new one [default: the wallet's default chain]
+    #[serde(default)]
+    pub from: Option<String>,
     pub owners: Vec<String>,
+    /// Weight of each owner in `owners`, in the same order
+    #[serde(default)]
+    pub owner_weights: Option<Vec<u64>>,
+    /// Rounds in which every owner may propose a block
+    #[serde(default)]
+    pub multi_leader_rounds: Option<u32>,
+    /// Tokens moved from `from` to the new chain
+    #[serde(default)]
+    pub initial_balance: Option<String>,
+}
+
+/// A newly opened chain
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ChainOpened {
+    pub chain_id: String,
+    /// Message that created the chain, as printed by linera versions that report it
+    pub message_id: Option<String>,
+}
+
+/// Answer to closing a chain
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "status", rename = "closed")]
+pub struct ChainClosed {
+    pub chain_id: String,
+}
+
+/// Output stream of the managed service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Severity of a tracing-formatted log line, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A single line of output captured from the managed service
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LogLine {
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub stream: LogStream,
+    pub level: Option<LogLevel>,
+    pub line: String,
 }
@@ -0,0 +1,180 @@
+use axum::{response::IntoResponse, Json};
+use serde_json::Value;
+use utoipa::openapi::schema::{ObjectBuilder, Schema, Type};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{OpenApi as OpenApiDoc, RefOr, Response, ResponseBuilder};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+use crate::models::{ErrorCode, API_PREFIX};
+
+/// Body of every failed request
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct ErrorResponse {
+    /// Always `false`
+    success: bool,
+    error: String,
+    code: ErrorCode,
+    /// Structured details, only for some errors
+    data: Option<Value>,
+}
+
+/// Query for the service's system API
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct SystemGraphQLRequest {
+    query: String,
+    variables: Option<Value>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Linera REST API",
+        description = "Manage a linera service, its wallet and chains.\n\n\
+            Every route under `/service`, `/wallet`, `/owner`, `/chains`, `/transfer` \
+            and `/graphql` also exists under `/v1/instances/{name}` to act on a named \
+            instance instead of the default one.\n\n\
+            The same routes without the `/v1` prefix are deprecated aliases."
+    ),
+    paths(
+        crate::health_check,
+        crate::list_instances,
+        crate::create_instance,
+        crate::get_instance,
+        crate::delete_instance,
+        crate::start_service,
+        crate::stop_service,
+        crate::get_status,
+        crate::get_logs,
+        crate::stream_logs,
+        crate::stream_logs_ws,
+        crate::init_wallet,
+        crate::request_chain,
+        crate::set_default_chain,
+        crate::forget_chain,
+        crate::get_wallet_info,
+        crate::keygen,
+        crate::add_owner,
+        crate::remove_owner,
+        crate::list_chains,
+        crate::open_chain,
+        crate::open_multi_owner_chain,
+        crate::close_chain,
+        crate::get_chain_owners,
+        crate::get_balance,
+        crate::transfer,
+        crate::proxy_graphql,
+        crate::proxy_system_graphql,
+    ),
+    modifiers(&BearerAuth, &StatusTags, &Versioned),
+    tags(
+        (name = "service", description = "The linera service process"),
+        (name = "wallet", description = "The instance's wallet"),
+        (name = "owner", description = "Chain ownership"),
+        (name = "chains", description = "Chains of the wallet, balances and transfers"),
+        (name = "graphql", description = "Queries forwarded to the service"),
+        (name = "instances", description = "Named instances with their own wallets"),
+        (name = "health"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the bearer token scheme and the 401/403 answers of every protected route
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+
+        let unauthorized = error_response("Missing or unknown API token");
+        let forbidden = error_response("The token's scope does not allow this request");
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if path == "/health" {
+                continue;
+            }
+            for operation in [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.delete,
+                &mut item.patch,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                operation.security =
+                    Some(vec![utoipa::openapi::security::SecurityRequirement::new(
+                        "bearer",
+                        Vec::<String>::new(),
+                    )]);
+                let responses = &mut operation.responses.responses;
+                responses.insert("401".to_string(), unauthorized.clone());
+                responses.insert("403".to_string(), forbidden.clone());
+            }
+        }
+    }
+}
+
+/// Adds the `status` field that `#[serde(tag = "status")]` writes on these structs
+struct StatusTags;
+
+impl Modify for StatusTags {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let Some(components) = openapi.components.as_mut() else {
+            return;
+        };
+        for (schema, status) in [
+            ("ServiceStarted", "started"),
+            ("StopOutcome", "stopped"),
+            ("InstanceRemoved", "removed"),
+            ("ChainClosed", "closed"),
+        ] {
+            if let Some(RefOr::T(Schema::Object(object))) = components.schemas.get_mut(schema) {
+                let tag = ObjectBuilder::new()
+                    .schema_type(Type::String)
+                    .enum_values(Some([status]))
+                    .build();
+                object.properties.insert("status".to_string(), tag.into());
+                object.required.push("status".to_string());
+            }
+        }
+    }
+}
+
+/// Puts every path under the version prefix the router mounts them on
+struct Versioned;
+
+impl Modify for Versioned {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let paths = std::mem::take(&mut openapi.paths.paths);
+        openapi.paths.paths = paths
+            .into_iter()
+            .map(|(path, item)| (format!("{}{}", API_PREFIX, path), item))
+            .collect();
+    }
+}
+
+fn error_response(description: &str) -> RefOr<Response> {
+    ResponseBuilder::new()
+        .description(description)
+        .content(
+            "application/json",
+            utoipa::openapi::ContentBuilder::new()
+                .schema(Some(RefOr::Ref(utoipa::openapi::Ref::from_schema_name(
+                    "ErrorResponse",
+                ))))
+                .build(),
+        )
+        .build()
+        .into()
+}
+
+/// The OpenAPI document of this server
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(ApiDoc::openapi())
+}
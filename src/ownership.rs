@@ -0,0 +1,234 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::accounts::validate_owner;
+use crate::error::ApiError;
+use crate::graphql_proxy::GraphQLProxy;
+use crate::models::{ChainOwnership, OwnerWeight, TimeoutConfig};
+use crate::wallet::validate_chain_id;
+
+/// Weight of owners added without one, as `linera open-multi-owner-chain` uses
+pub const DEFAULT_OWNER_WEIGHT: u64 = 100;
+
+/// The chain's `ChainOwnership`, which the service returns as a JSON scalar
+const OWNERSHIP_QUERY: &str = "query Ownership($chainId: ChainId!) { \
+    chain(chainId: $chainId) { executionState { system { ownership } } } }";
+
+/// `ChainOwnership` as linera serializes it
+#[derive(Deserialize)]
+struct RawOwnership {
+    #[serde(default)]
+    super_owners: Vec<String>,
+    #[serde(default)]
+    owners: BTreeMap<String, u64>,
+    #[serde(default)]
+    multi_leader_rounds: u32,
+    #[serde(default)]
+    open_multi_leader_rounds: bool,
+    #[serde(default)]
+    timeout_config: RawTimeoutConfig,
+}
+
+/// `TimeoutConfig` as linera serializes it, with durations in microseconds
+#[derive(Deserialize, Default)]
+struct RawTimeoutConfig {
+    fast_round_duration: Option<u64>,
+    base_timeout: Option<u64>,
+    timeout_increment: Option<u64>,
+    fallback_duration: Option<u64>,
+}
+
+impl RawTimeoutConfig {
+    fn into_millis(self) -> TimeoutConfig {
+        let millis = |micros: Option<u64>| micros.map(|micros| micros / 1000);
+        TimeoutConfig {
+            fast_round_ms: millis(self.fast_round_duration),
+            base_timeout_ms: millis(self.base_timeout),
+            timeout_increment_ms: millis(self.timeout_increment),
+            fallback_duration_ms: millis(self.fallback_duration),
+        }
+    }
+}
+
+/// Current ownership of `chain_id`, read from the running service
+pub async fn query(proxy: &GraphQLProxy, chain_id: &str) -> Result<ChainOwnership> {
+    validate_chain_id(chain_id)?;
+    let response = proxy
+        .system_query(OWNERSHIP_QUERY, Some(json!({ "chainId": chain_id })))
+        .await?;
+    Ok(parse(chain_id, &response)?)
+}
+
+fn parse(chain_id: &str, response: &Value) -> Result<ChainOwnership, ApiError> {
+    if let Some(error) = response["errors"]
+        .as_array()
+        .and_then(|errors| errors.first())
+    {
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        return Err(ApiError::GraphQL(message.to_string()));
+    }
+
+    let ownership = response
+        .pointer("/data/chain/executionState/system/ownership")
+        .filter(|ownership| !ownership.is_null())
+        .ok_or_else(|| ApiError::GraphQL(format!("No ownership of chain {}", chain_id)))?;
+    let raw: RawOwnership = serde_json::from_value(ownership.clone())
+        .map_err(|e| ApiError::GraphQL(format!("Unexpected ownership: {}", e)))?;
+
+    Ok(ChainOwnership {
+        chain_id: chain_id.to_string(),
+        super_owners: raw.super_owners,
+        owners: raw
+            .owners
+            .into_iter()
+            .map(|(owner, weight)| OwnerWeight { owner, weight })
+            .collect(),
+        multi_leader_rounds: raw.multi_leader_rounds,
+        open_multi_leader_rounds: raw.open_multi_leader_rounds,
+        timeout_config: raw.timeout_config.into_millis(),
+    })
+}
+
+/// Reject owner lists before the chain's ownership is queried
+pub fn validate_owners(owners: &[String]) -> Result<(), ApiError> {
+    if owners.is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "At least one owner is required".to_string(),
+        ));
+    }
+    owners.iter().try_for_each(|owner| validate_owner(owner))
+}
+
+/// `current` with `owners` added at `weight`; owners it already has keep their role
+pub fn with_owners(current: &ChainOwnership, owners: &[String], weight: u64) -> ChainOwnership {
+    let mut ownership = current.clone();
+    for owner in owners {
+        if !is_owner(&ownership, owner) {
+            ownership.owners.push(OwnerWeight {
+                owner: owner.clone(),
+                weight,
+            });
+        }
+    }
+    ownership
+}
+
+/// `current` without `owners`, refusing changes that leave the chain without owners
+pub fn without_owners(
+    current: &ChainOwnership,
+    owners: &[String],
+) -> Result<ChainOwnership, ApiError> {
+    for owner in owners {
+        if !is_owner(current, owner) {
+            return Err(ApiError::InvalidRequest(format!(
+                "{} is not an owner of chain {}",
+                owner, current.chain_id
+            )));
+        }
+    }
+
+    let mut ownership = current.clone();
+    ownership
+        .super_owners
+        .retain(|owner| !owners.contains(owner));
+    ownership
+        .owners
+        .retain(|owner| !owners.contains(&owner.owner));
+    if ownership.super_owners.is_empty() && ownership.owners.is_empty() {
+        return Err(ApiError::InvalidRequest(format!(
+            "Chain {} would be left without owners",
+            current.chain_id
+        )));
+    }
+    Ok(ownership)
+}
+
+fn is_owner(ownership: &ChainOwnership, owner: &str) -> bool {
+    ownership.super_owners.iter().any(|o| o == owner)
+        || ownership.owners.iter().any(|o| o.owner == owner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN_ID: &str = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4";
+    const ALICE: &str = "0x3a6ba9e1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5";
+    const BOB: &str = "0x7c0ffee1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5";
+    const CAROL: &str = "0xc0ffee254729296a45a3885639ac7e10f9d54979";
+
+    fn ownership() -> ChainOwnership {
+        let response = json!({
+            "data": { "chain": { "executionState": { "system": { "ownership": {
+                "super_owners": [ALICE],
+                "owners": { BOB: 100 },
+                "multi_leader_rounds": 10,
+                "open_multi_leader_rounds": false,
+                "timeout_config": { "base_timeout": 10000000 }
+            } } } } }
+        });
+        parse(CHAIN_ID, &response).unwrap()
+    }
+
+    #[test]
+    fn parses_owners_and_weights() {
+        let ownership = ownership();
+
+        assert_eq!(ownership.super_owners, [ALICE]);
+        assert_eq!(
+            ownership.owners,
+            [OwnerWeight {
+                owner: BOB.to_string(),
+                weight: 100
+            }]
+        );
+        assert_eq!(ownership.multi_leader_rounds, 10);
+        assert_eq!(ownership.timeout_config.base_timeout_ms, Some(10_000));
+        assert_eq!(ownership.timeout_config.fast_round_ms, None);
+    }
+
+    #[test]
+    fn reports_graphql_errors() {
+        let response = json!({ "data": null, "errors": [{ "message": "Chain not found" }] });
+        let err = parse(CHAIN_ID, &response).unwrap_err();
+        assert!(err.to_string().contains("Chain not found"));
+
+        let response = json!({ "data": { "chain": null } });
+        assert!(parse(CHAIN_ID, &response).is_err());
+    }
+
+    #[test]
+    fn adding_keeps_existing_owners() {
+        let ownership = with_owners(&ownership(), &[CAROL.to_string(), BOB.to_string()], 5);
+
+        assert_eq!(ownership.super_owners, [ALICE]);
+        let owners: Vec<_> = ownership
+            .owners
+            .iter()
+            .map(|o| (o.owner.as_str(), o.weight))
+            .collect();
+        assert_eq!(owners, [(BOB, 100), (CAROL, 5)]);
+    }
+
+    #[test]
+    fn validates_owners() {
+        assert!(validate_owners(&[ALICE.to_string(), CAROL.to_string()]).is_ok());
+        assert!(validate_owners(&[]).is_err());
+        assert!(validate_owners(&["0xaa".to_string()]).is_err());
+    }
+
+    #[test]
+    fn removing_refuses_to_leave_no_owner() {
+        let ownership = without_owners(&ownership(), &[ALICE.to_string()]).unwrap();
+        assert!(ownership.super_owners.is_empty());
+        assert_eq!(ownership.owners.len(), 1);
+
+        let err = without_owners(&ownership, &[BOB.to_string()]).unwrap_err();
+        assert!(err.to_string().contains("without owners"));
+
+        let err = without_owners(&ownership, &[CAROL.to_string()]).unwrap_err();
+        assert!(err.to_string().contains("not an owner"));
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+use crate::models::{LogLevel, LogLine, LogStream};
+
+/// Default number of lines kept in memory
+pub const DEFAULT_BUFFER_LINES: usize = 5000;
+
+/// Default size at which the log file is rotated
+pub const DEFAULT_LOG_FILE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Lines a live subscriber may fall behind before it starts missing some
+const SUBSCRIBER_CAPACITY: usize = 1024;
+
+/// Longest line kept from the service's output; the rest of a longer line is dropped
+const MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// Bounded in-memory log of the managed service's output, optionally mirrored to a file
+pub struct ServiceLogs {
+    inner: Mutex<Inner>,
+    /// Written from the blocking pool so slow disks do not stall the runtime
+    file: Option<Arc<Mutex<RotatingFile>>>,
+    capacity: usize,
+    live: broadcast::Sender<LogLine>,
+}
+
+struct Inner {
+    lines: VecDeque<LogLine>,
+    next_seq: u64,
+}
+
+impl ServiceLogs {
+    pub fn new(capacity: usize, log_file: Option<PathBuf>, max_file_bytes: u64) -> Self {
+        let file =
+            log_file.and_then(
+                |path| match RotatingFile::open(path.clone(), max_file_bytes) {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        error!("Failed to open service log file {}: {}", path.display(), e);
+                        None
+                    }
+                },
+            );
+
+        Self {
+            inner: Mutex::new(Inner {
+                lines: VecDeque::with_capacity(capacity.min(1024)),
+                next_seq: 0,
+            }),
+            file: file.map(|file| Arc::new(Mutex::new(file))),
+            capacity: capacity.max(1),
+            live: broadcast::channel(SUBSCRIBER_CAPACITY).0,
+        }
+    }
+
+    /// Receive every line appended from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.live.subscribe()
+    }
+
+    /// Sequence number the next appended line will get
+    pub fn next_seq(&self) -> u64 {
+        self.inner.lock().unwrap().next_seq
+    }
+
+    /// Append a line, evicting the oldest one when the buffer is full, and mirror it to the file
+    pub async fn push(&self, stream: LogStream, line: String) {
+        let entry = self.append(stream, line);
+
+        if let Some(file) = &self.file {
+            let file = file.clone();
+            let written =
+                tokio::task::spawn_blocking(move || file.lock().unwrap().write_line(&entry)).await;
+            if let Err(e) = written {
+                error!("Failed to write service log file: {}", e);
+            }
+        }
+    }
+
+    fn append(&self, stream: LogStream, line: String) -> LogLine {
+        let line = strip_ansi(&line);
+        let mut inner = self.inner.lock().unwrap();
+
+        let entry = LogLine {
+            seq: inner.next_seq,
+            timestamp_ms: now_ms(),
+            stream,
+            level: parse_level(&line),
+            line,
+        };
+        inner.next_seq += 1;
+
+        if inner.lines.len() == self.capacity {
+            inner.lines.pop_front();
+        }
+        inner.lines.push_back(entry.clone());
+
+        // Nobody listening is not an error
+        let _ = self.live.send(entry.clone());
+        entry
+    }
+
+    /// Last `lines` entries, optionally restricted to one stream and to lines at or after `since`
+    pub fn tail(
+        &self,
+        lines: usize,
+        stream: Option<LogStream>,
+        since: Option<u64>,
+    ) -> Vec<LogLine> {
+        self.tail_matching(lines, |l| {
+            stream.is_none_or(|s| l.stream == s) && since.is_none_or(|seq| l.seq >= seq)
+        })
+    }
+
+    /// Last `lines` entries accepted by `filter`
+    pub fn tail_matching(&self, lines: usize, filter: impl Fn(&LogLine) -> bool) -> Vec<LogLine> {
+        let inner = self.inner.lock().unwrap();
+
+        let mut out: Vec<LogLine> = inner
+            .lines
+            .iter()
+            .rev()
+            .filter(|l| filter(l))
+            .take(lines)
+            .cloned()
+            .collect();
+        out.reverse();
+        out
+    }
+}
+
+/// Extract the level from a tracing-formatted line such as
+/// `2024-05-01T10:00:00.000000Z  INFO linera_service: message`
+fn parse_level(line: &str) -> Option<LogLevel> {
+    line.split_whitespace()
+        .take(3)
+        .find_map(|token| match token {
+            "TRACE" => Some(LogLevel::Trace),
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARN" => Some(LogLevel::Warn),
+            "ERROR" => Some(LogLevel::Error),
+            _ => None,
+        })
+}
+
+/// Remove terminal color escape sequences
+pub(crate) fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip `ESC [ ... <final byte>`
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+/// Spawn a task that reads `reader` line by line into `logs` until EOF
+pub fn spawn_drain<R>(reader: R, stream: LogStream, logs: Arc<ServiceLogs>) -> JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            match read_line_capped(&mut reader, &mut buf, MAX_LINE_BYTES).await {
+                Ok(None) => break,
+                Ok(Some(truncated)) => {
+                    let line = String::from_utf8_lossy(&buf);
+                    let mut line = line.trim_end_matches(['\r', '\n']).to_string();
+                    if truncated {
+                        line.push_str(" [truncated]");
+                    }
+                    logs.push(stream, line).await;
+                }
+                Err(e) => {
+                    warn!("Failed to read service {:?}: {}", stream, e);
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Read the next line into `buf`, keeping at most `max` bytes of it and skipping the rest.
+///
+/// Returns whether the line was cut short, or `None` at EOF.
+async fn read_line_capped<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max: usize,
+) -> std::io::Result<Option<bool>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut read_any = false;
+    let mut truncated = false;
+
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(read_any.then_some(truncated));
+        }
+        read_any = true;
+
+        let newline = available.iter().position(|b| *b == b'\n');
+        let content = &available[..newline.unwrap_or(available.len())];
+        let room = max.saturating_sub(buf.len());
+        truncated |= content.len() > room;
+        buf.extend_from_slice(&content[..content.len().min(room)]);
+
+        let used = newline.map_or(available.len(), |at| at + 1);
+        reader.consume(used);
+        if newline.is_some() {
+            return Ok(Some(truncated));
+        }
+    }
+}
+
+/// Log file that is renamed to `<path>.1` once it grows past a size limit
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+        })
+    }
+
+    fn write_line(&mut self, entry: &LogLine) {
+        let text = format!("[{}] {}\n", entry.stream.as_str(), entry.line);
+
+        if self.written + text.len() as u64 > self.max_bytes {
+            if let Err(e) = self.rotate() {
+                error!("Failed to rotate service log file: {}", e);
+            }
+        }
+
+        match self.file.write_all(text.as_bytes()) {
+            Ok(()) => self.written += text.len() as u64,
+            Err(e) => error!("Failed to write service log file: {}", e),
+        }
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        std::fs::rename(&self.path, rotated)?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn drain(output: &str, logs: &Arc<ServiceLogs>) {
+        let reader = std::io::Cursor::new(output.as_bytes().to_vec());
+        spawn_drain(reader, LogStream::Stderr, logs.clone())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn long_lines_are_cut_short() {
+        let logs = Arc::new(ServiceLogs::new(10, None, DEFAULT_LOG_FILE_MAX_BYTES));
+        let long = "x".repeat(MAX_LINE_BYTES * 3);
+
+        drain(&format!("first\r\n{}\nlast", long), &logs).await;
+
+        let lines: Vec<_> = logs
+            .tail(10, None, None)
+            .into_iter()
+            .map(|l| l.line)
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "first");
+        assert_eq!(lines[1], format!("{} [truncated]", &long[..MAX_LINE_BYTES]));
+        assert_eq!(lines[2], "last");
+    }
+
+    #[tokio::test]
+    async fn lines_are_mirrored_to_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("service.log");
+        let logs = Arc::new(ServiceLogs::new(
+            10,
+            Some(path.clone()),
+            DEFAULT_LOG_FILE_MAX_BYTES,
+        ));
+
+        drain("WARN linera: low balance\n", &logs).await;
+
+        let text = std::fs::read_to_string(path).unwrap();
+        assert_eq!(text, "[stderr] WARN linera: low balance\n");
+        assert_eq!(logs.tail(1, None, None)[0].level, Some(LogLevel::Warn));
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process::ExitStatus;
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::models::{ExitInfo, ShutdownSignal};
+use crate::service_logs::now_ms;
+
+/// Interval at which an adopted process is checked for exit
+const ADOPTED_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A linera service process, either spawned by us or adopted from a previous server run
+pub enum ServiceProcess {
+    /// Spawned by this server, with its output pipes
+    Child(Child),
+    /// Left running by a previous server instance; only its pid is known
+    Adopted(u32),
+}
+
+impl ServiceProcess {
+    pub fn id(&self) -> Option<u32> {
+        match self {
+            ServiceProcess::Child(child) => child.id(),
+            ServiceProcess::Adopted(pid) => Some(*pid),
+        }
+    }
+
+    /// Exit information if the process has exited, without blocking
+    pub fn try_wait(&mut self) -> std::io::Result<Option<ExitInfo>> {
+        match self {
+            ServiceProcess::Child(child) => Ok(child.try_wait()?.map(|s| exit_info(&s))),
+            // Not our child, so its exit status is not ours to collect
+            ServiceProcess::Adopted(pid) => Ok((!is_alive(*pid)).then(unknown_exit)),
+        }
+    }
+
+    /// Ask the process to exit with SIGTERM and kill it if still alive after `grace_period`
+    pub async fn terminate(
+        &mut self,
+        grace_period: Duration,
+    ) -> Result<(ShutdownSignal, ExitInfo)> {
+        match self {
+            ServiceProcess::Child(child) => {
+                #[cfg(unix)]
+                if let Some(pid) = child.id() {
+                    if send_signal(pid, libc::SIGTERM) {
+                        match tokio::time::timeout(grace_period, child.wait()).await {
+                            Ok(status) => {
+                                return Ok((ShutdownSignal::Sigterm, exit_info(&status?)))
+                            }
+                            Err(_) => warn!(
+                                "Linera service still running {:?} after SIGTERM, killing it",
+                                grace_period
+                            ),
+                        }
+                    }
+                }
+
+                child.kill().await?;
+                Ok((ShutdownSignal::Sigkill, exit_info(&child.wait().await?)))
+            }
+            ServiceProcess::Adopted(pid) => {
+                let pid = *pid;
+
+                #[cfg(unix)]
+                {
+                    if send_signal(pid, libc::SIGTERM) && wait_gone(pid, grace_period).await {
+                        return Ok((ShutdownSignal::Sigterm, unknown_exit()));
+                    }
+                    warn!(
+                        "Linera service still running {:?} after SIGTERM, killing it",
+                        grace_period
+                    );
+                    send_signal(pid, libc::SIGKILL);
+                    wait_gone(pid, grace_period).await;
+                }
+
+                Ok((ShutdownSignal::Sigkill, unknown_exit()))
+            }
+        }
+    }
+}
+
+/// Whether `pid` is a live (non-zombie) process
+pub fn is_alive(pid: u32) -> bool {
+    match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        // The state follows the parenthesized command name, which may itself contain spaces
+        Ok(stat) => stat
+            .rsplit_once(')')
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .is_some_and(|state| state != "Z" && state != "X"),
+        Err(_) => false,
+    }
+}
+
+/// Command line of a running process
+pub fn cmdline(pid: u32) -> Option<Vec<String>> {
+    let raw = std::fs::read(Path::new("/proc").join(pid.to_string()).join("cmdline")).ok()?;
+
+    Some(
+        raw.split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect(),
+    )
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> bool {
+    // SAFETY: kill(2) has no memory-safety requirements
+    unsafe { libc::kill(pid as libc::pid_t, signal) == 0 }
+}
+
+#[cfg(unix)]
+async fn wait_gone(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while is_alive(pid) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(ADOPTED_POLL_INTERVAL).await;
+    }
+    true
+}
+
+pub fn exit_info(status: &ExitStatus) -> ExitInfo {
+    #[cfg(unix)]
+    let signal = std::os::unix::process::ExitStatusExt::signal(status);
+    #[cfg(not(unix))]
+    let signal = None;
+
+    ExitInfo {
+        code: status.code(),
+        signal,
+        timestamp_ms: now_ms(),
+    }
+}
+
+fn unknown_exit() -> ExitInfo {
+    ExitInfo {
+        code: None,
+        signal: None,
+        timestamp_ms: now_ms(),
+    }
+}
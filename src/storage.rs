@@ -0,0 +1,67 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::ApiError;
+
+/// Default storage for the linera service (no persistence)
+pub const DEFAULT_STORAGE: &str = "memory";
+
+/// Check a `--storage` value before handing it to `linera service`.
+///
+/// `memory` needs nothing; `rocksdb:<path>[:...]` needs a writable directory
+/// (or a writable parent to create it in); anything else is passed through as is.
+pub fn validate(storage: &str) -> Result<(), ApiError> {
+    let storage = storage.trim();
+    if storage.is_empty() {
+        return Err(ApiError::InvalidStorage(
+            "Storage must not be empty".to_string(),
+        ));
+    }
+
+    if let Some(path) = rocksdb_path(storage) {
+        let path = path.as_path();
+        let dir = if path.exists() {
+            if !path.is_dir() {
+                return Err(ApiError::InvalidStorage(format!(
+                    "RocksDB path {} is not a directory",
+                    path.display()
+                )));
+            }
+            path
+        } else {
+            path.parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+        };
+
+        if !dir.is_dir() {
+            return Err(ApiError::InvalidStorage(format!(
+                "Storage directory {} does not exist",
+                dir.display()
+            )));
+        }
+        check_writable(dir)?;
+    }
+
+    Ok(())
+}
+
+/// Directory of a `rocksdb:` storage value
+fn rocksdb_path(storage: &str) -> Option<PathBuf> {
+    let rest = storage.strip_prefix("rocksdb:")?;
+    let path = rest.split(':').next().unwrap_or(rest);
+    Some(PathBuf::from(path))
+}
+
+fn check_writable(dir: &Path) -> Result<(), ApiError> {
+    let probe = dir.join(format!(".linera-rest-server-probe-{}", std::process::id()));
+
+    std::fs::write(&probe, b"").map_err(|e| {
+        ApiError::InvalidStorage(format!(
+            "Storage directory {} is not writable: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use axum::{routing::get, Router};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::process::{Child, Command};
+
+use crate::error::ApiError;
+use crate::linera_cli::{CliOutput, LineraCli};
+use crate::service_process::is_alive;
+
+/// Answer to a scripted command, given its arguments without the global options
+type Script = Arc<dyn Fn(&[String]) -> CliOutput + Send + Sync>;
+
+/// Options that come before the subcommand and are ignored when matching scripts
+const GLOBAL_OPTIONS: [&str; 3] = ["--wallet", "--keystore", "--storage"];
+
+impl CliOutput {
+    pub fn ok(stdout: &str) -> Self {
+        Self {
+            code: Some(0),
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+        }
+    }
+
+    pub fn failed(code: i32, stderr: &str) -> Self {
+        Self {
+            code: Some(code),
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+        }
+    }
+}
+
+/// Scripted stand-in for the linera binary.
+///
+/// Commands are matched on their subcommand words (`"wallet show"`), ignoring
+/// the global `--wallet`/`--keystore`/`--storage` options; every call is recorded.
+/// Like the real binary, a successful `wallet init` creates the wallet file.
+#[derive(Default)]
+pub struct FakeLinera {
+    scripts: Vec<(Vec<String>, Script)>,
+    service: Option<FakeService>,
+    missing: bool,
+    calls: Mutex<Vec<Vec<String>>>,
+    service_pids: Mutex<Vec<u32>>,
+}
+
+impl FakeLinera {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `command` with `output` every time it is run
+    pub fn on(self, command: &str, output: CliOutput) -> Self {
+        self.on_call(command, move |_| output.clone())
+    }
+
+    /// Answer `command` with what `script` returns for its arguments, e.g. to
+    /// update other fakes the way the real command would
+    pub fn on_call(
+        mut self,
+        command: &str,
+        script: impl Fn(&[String]) -> CliOutput + Send + Sync + 'static,
+    ) -> Self {
+        let words = command.split_whitespace().map(String::from).collect();
+        self.scripts.push((words, Arc::new(script)));
+        self
+    }
+
+    /// What `linera service` does when spawned
+    pub fn service(mut self, service: FakeService) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Behave as if the binary did not exist
+    pub fn missing() -> Self {
+        Self {
+            missing: true,
+            ..Self::default()
+        }
+    }
+
+    /// Arguments of every command run or spawned so far, without the global options
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Pids of every `linera service` process spawned so far
+    pub fn service_pids(&self) -> Vec<u32> {
+        self.service_pids.lock().unwrap().clone()
+    }
+
+    fn record(&self, args: &[String]) -> Result<Vec<String>> {
+        if self.missing {
+            return Err(ApiError::BinaryMissing("fake-linera".to_string()).into());
+        }
+
+        let command = strip_global_options(args);
+        self.calls.lock().unwrap().push(command.clone());
+        Ok(command)
+    }
+}
+
+#[async_trait]
+impl LineraCli for FakeLinera {
+    async fn run(&self, args: &[String]) -> Result<CliOutput> {
+        let command = self.record(args)?;
+
+        let output = self
+            .scripts
+            .iter()
+            .find(|(words, _)| command.starts_with(words))
+            .map(|(_, script)| script(&command))
+            .ok_or_else(|| anyhow!("Unscripted linera command: {}", command.join(" ")))?;
+
+        if output.success() && command.starts_with(&["wallet".to_string(), "init".to_string()]) {
+            if let Some(wallet) = option_value(args, "--wallet") {
+                std::fs::write(wallet, "{}")?;
+            }
+        }
+        Ok(output)
+    }
+
+    fn spawn_service(&self, args: &[String]) -> Result<Child> {
+        let command = self.record(args)?;
+        let service = self
+            .service
+            .as_ref()
+            .ok_or_else(|| anyhow!("Unscripted linera command: {}", command.join(" ")))?;
+
+        let child = service.spawn(option_value(&command, "--port").and_then(|p| p.parse().ok()))?;
+        self.service_pids.lock().unwrap().extend(child.id());
+        Ok(child)
+    }
+}
+
+/// A `linera service` process: prints some output, then exits or keeps running
+#[derive(Clone, Default)]
+pub struct FakeService {
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    exit_code: Option<i32>,
+    /// Served on the service port while the process runs
+    endpoint: Option<Router>,
+}
+
+impl FakeService {
+    /// Answers the readiness probe on its port until it is stopped
+    pub fn ready() -> Self {
+        Self::serving(Router::new().route("/", get(|| async { "GraphiQL" })))
+    }
+
+    /// Serves `endpoint`, e.g. a [`MockGraphQL`](super::MockGraphQL) router, on its port
+    /// until it is stopped
+    pub fn serving(endpoint: Router) -> Self {
+        Self {
+            endpoint: Some(endpoint),
+            ..Self::default()
+        }
+    }
+
+    /// Exits with `code` without ever becoming ready
+    pub fn exits(code: i32) -> Self {
+        Self {
+            exit_code: Some(code),
+            ..Self::default()
+        }
+    }
+
+    /// Never becomes ready and never exits
+    pub fn hangs() -> Self {
+        Self::default()
+    }
+
+    pub fn stdout(mut self, line: &str) -> Self {
+        self.stdout.push(line.to_string());
+        self
+    }
+
+    pub fn stderr(mut self, line: &str) -> Self {
+        self.stderr.push(line.to_string());
+        self
+    }
+
+    /// Start the process as `linera service --port <port>` would; it ends up as `sleep 3600`
+    /// unless it exits
+    pub fn spawn(&self, port: Option<u16>) -> Result<Child> {
+        let end = match self.exit_code {
+            Some(code) => format!("exit {}", code),
+            None => "exec sleep 3600".to_string(),
+        };
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "printf '%s' \"$FAKE_STDOUT\"; printf '%s' \"$FAKE_STDERR\" >&2; {}",
+                end
+            ))
+            .env("FAKE_STDOUT", lines(&self.stdout))
+            .env("FAKE_STDERR", lines(&self.stderr))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        if let Some(endpoint) = &self.endpoint {
+            let port = port.ok_or_else(|| anyhow!("linera service spawned without --port"))?;
+            serve_until_exit(port, child.id(), endpoint.clone())?;
+        }
+
+        Ok(child)
+    }
+}
+
+/// Serve `app` on `port` while `pid` is alive
+fn serve_until_exit(port: u16, pid: Option<u32>, app: Router) -> Result<()> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))?;
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+
+    let exited = async move {
+        while pid.is_some_and(is_alive) {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    };
+
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app)
+            .with_graceful_shutdown(exited)
+            .await;
+    });
+    Ok(())
+}
+
+fn strip_global_options(args: &[String]) -> Vec<String> {
+    let mut command = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if GLOBAL_OPTIONS.contains(&arg.as_str()) {
+            args.next();
+        } else {
+            command.push(arg.clone());
+        }
+    }
+    command
+}
+
+fn option_value<'a>(args: &'a [String], option: &str) -> Option<&'a str> {
+    let at = args.iter().position(|arg| arg == option)?;
+    args.get(at + 1).map(String::as_str)
+}
+
+fn lines(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::State,
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Canned answer of the mock GraphQL server
+#[derive(Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    body: String,
+    delay: Duration,
+}
+
+impl MockResponse {
+    pub fn json(body: Value) -> Self {
+        Self {
+            status: StatusCode::OK,
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Any status with a raw (possibly non-JSON) body
+    pub fn status(status: StatusCode, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Wait this long before answering
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request received by the mock
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub path: String,
+    pub body: Value,
+}
+
+/// Imitation of the GraphQL endpoints of `linera service`: `/`,
+/// `/chains/{id}` and `/chains/{id}/applications/{app}`.
+///
+/// Responses are scripted per path; unscripted paths answer 404.
+#[derive(Clone, Default)]
+pub struct MockGraphQL {
+    responses: Arc<Mutex<HashMap<String, MockResponse>>>,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockGraphQL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer POSTs to `path` with `response`
+    pub fn respond(self, path: &str, response: MockResponse) -> Self {
+        self.responses
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), response);
+        self
+    }
+
+    /// Every GraphQL request received so far
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            // GET / is the GraphiQL page, which the readiness probe requests
+            .route("/", get(|| async { "GraphiQL" }).post(answer))
+            .route("/chains/:chain_id", post(answer))
+            .route(
+                "/chains/:chain_id/applications/:application_id",
+                post(answer),
+            )
+            .with_state(self.clone())
+    }
+
+    /// Serve on an ephemeral local port and return it
+    pub async fn start(&self) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = self.router();
+
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        port
+    }
+}
+
+async fn answer(State(mock): State<MockGraphQL>, uri: Uri, Json(body): Json<Value>) -> Response {
+    let path = uri.path().to_string();
+    mock.requests.lock().unwrap().push(MockRequest {
+        path: path.clone(),
+        body,
+    });
+
+    let response = mock.responses.lock().unwrap().get(&path).cloned();
+    match response {
+        Some(response) => {
+            tokio::time::sleep(response.delay).await;
+            (
+                response.status,
+                [("content-type", "application/json")],
+                response.body,
+            )
+                .into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("No mock response for {}", path),
+        )
+            .into_response(),
+    }
+}
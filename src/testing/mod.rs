@@ -0,0 +1,7 @@
+//! Test doubles for the linera binary and its service
+
+mod fake_linera;
+mod mock_graphql;
+
+pub use fake_linera::{FakeLinera, FakeService};
+pub use mock_graphql::{MockGraphQL, MockResponse};
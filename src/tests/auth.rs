@@ -0,0 +1,119 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use super::{TestApp, ADMIN_TOKEN, READ_TOKEN};
+use crate::linera_cli::CliOutput;
+use crate::testing::FakeLinera;
+
+#[tokio::test]
+async fn requests_without_token_are_rejected() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app
+        .request_as(None, Method::GET, "/v1/service/status", None)
+        .await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "unauthorized");
+}
+
+#[tokio::test]
+async fn unknown_token_is_rejected() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, _) = app
+        .request_as(Some("guess"), Method::GET, "/v1/service/status", None)
+        .await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn read_token_can_read_but_not_change() {
+    let app = TestApp::new(FakeLinera::new().on("keygen", CliOutput::ok("0xabc\n")));
+
+    let (status, _) = app
+        .request_as(Some(READ_TOKEN), Method::GET, "/v1/service/status", None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = app
+        .request_as(
+            Some(READ_TOKEN),
+            Method::POST,
+            "/v1/wallet/keygen",
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "forbidden");
+    assert!(app.linera.calls().is_empty());
+
+    let (status, _) = app
+        .request_as(
+            Some(ADMIN_TOKEN),
+            Method::POST,
+            "/v1/wallet/keygen",
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn query_token_only_works_for_reads() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, _) = app
+        .request_as(
+            None,
+            Method::GET,
+            &format!("/v1/service/status?access_token={}", READ_TOKEN),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .request_as(
+            None,
+            Method::POST,
+            &format!("/v1/service/stop?access_token={}", ADMIN_TOKEN),
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn named_instance_routes_need_a_token() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, _) = app
+        .request_as(
+            None,
+            Method::GET,
+            "/v1/instances/default/service/status",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = app
+        .request_as(None, Method::GET, "/v1/instances", None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn health_and_web_ui_are_public() {
+    let app = TestApp::new(FakeLinera::new());
+    std::fs::write(app.dir.path().join("index.html"), "<html></html>").unwrap();
+
+    let (status, body) = app.request_as(None, Method::GET, "/v1/health", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+
+    let (status, _) = app.request_as(None, Method::GET, "/index.html", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
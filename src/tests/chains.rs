@@ -0,0 +1,298 @@
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use super::{args, free_port, TestApp};
+use crate::linera_cli::CliOutput;
+use crate::testing::{FakeLinera, FakeService, MockGraphQL, MockResponse};
+
+const WALLET_SHOW: &str = include_str!("../../tests/fixtures/wallet_show_multi.txt");
+const CHAIN_ID: &str = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4";
+const OTHER_CHAIN_ID: &str = "e476187f6ddfeb9d588c7b45d3df334d5501d6499b3f9ad5595cae86cce16a65";
+const OWNER: &str = "0x3a6ba9e1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5";
+const CERTIFICATE: &str = "0c1d2e3f4a5b6c7d8e9f0a1b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2d3e4f5";
+
+#[tokio::test]
+async fn balance_includes_wallet_owner_by_default() {
+    let app = TestApp::new(
+        FakeLinera::new()
+            .on("wallet show", CliOutput::ok(WALLET_SHOW))
+            .on(
+                &format!("query-balance {}@{}", OWNER, CHAIN_ID),
+                CliOutput::ok("2.5\n"),
+            )
+            .on(
+                &format!("query-balance {}", CHAIN_ID),
+                CliOutput::ok("10.\n"),
+            ),
+    )
+    .with_wallet();
+
+    let (status, body) = app.get(&format!("/v1/chains/{}/balance", CHAIN_ID)).await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["data"],
+        json!({
+            "chain_id": CHAIN_ID,
+            "balance": "10",
+            "owners": [{ "owner": OWNER, "balance": "2.5" }]
+        })
+    );
+}
+
+#[tokio::test]
+async fn balance_of_named_owners_skips_wallet() {
+    let app =
+        TestApp::new(FakeLinera::new().on("query-balance", CliOutput::ok("1.\n"))).with_wallet();
+
+    let uri = format!(
+        "/v1/instances/default/chains/{}/balance?owners={}",
+        CHAIN_ID, OWNER
+    );
+    let (status, body) = app.get(&uri).await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["owners"][0]["owner"], OWNER);
+    assert_eq!(
+        app.linera.calls(),
+        [
+            args(&format!("query-balance {}", CHAIN_ID)),
+            args(&format!("query-balance {}@{}", OWNER, CHAIN_ID))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn balance_rejects_invalid_owner() {
+    let app = TestApp::new(FakeLinera::new()).with_wallet();
+
+    let uri = format!("/v1/chains/{}/balance?owners=alice", CHAIN_ID);
+    let (status, body) = app.get(&uri).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_owner");
+    assert!(app.linera.calls().is_empty());
+}
+
+/// Move 1.5 from the wallet's owner on `CHAIN_ID` to `OTHER_CHAIN_ID`
+async fn transfer(app: &TestApp) -> Value {
+    let (status, body) = app
+        .post(
+            "/v1/transfer",
+            json!({
+                "from": { "chain_id": CHAIN_ID, "owner": OWNER },
+                "to": { "chain_id": OTHER_CHAIN_ID },
+                "amount": "1.5"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["data"].clone()
+}
+
+#[tokio::test]
+async fn transfer_without_service_uses_cli() {
+    let app = TestApp::new(FakeLinera::new().on("transfer", CliOutput::ok(""))).with_wallet();
+
+    let result = transfer(&app).await;
+
+    // The CLI does not report the certificate
+    assert_eq!(result["certificate_hash"], Value::Null);
+    assert_eq!(
+        app.linera.calls(),
+        [args(&format!(
+            "transfer 1.5 --from {}@{} --to {}",
+            OWNER, CHAIN_ID, OTHER_CHAIN_ID
+        ))]
+    );
+}
+
+#[tokio::test]
+async fn transfer_goes_through_running_service() {
+    let mock = MockGraphQL::new().respond(
+        "/",
+        MockResponse::json(json!({ "data": { "transfer": CERTIFICATE } })),
+    );
+    let app =
+        TestApp::new(FakeLinera::new().service(FakeService::serving(mock.router()))).with_wallet();
+    let (status, body) = app
+        .post("/v1/service/start", json!({ "port": free_port() }))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let result = transfer(&app).await;
+
+    assert_eq!(result["certificate_hash"], CERTIFICATE);
+    let request = &mock.requests()[0].body;
+    assert!(request["query"]
+        .as_str()
+        .unwrap()
+        .starts_with("mutation Transfer"));
+    assert_eq!(
+        request["variables"],
+        json!({
+            "chainId": CHAIN_ID,
+            "owner": OWNER,
+            "recipient": { "chainId": OTHER_CHAIN_ID, "owner": "0x00" },
+            "amount": "1.5"
+        })
+    );
+    // Only `linera service` ran
+    assert_eq!(app.linera.calls().len(), 1);
+}
+
+#[tokio::test]
+async fn transfer_rejects_invalid_amount() {
+    let app = TestApp::new(FakeLinera::new()).with_wallet();
+
+    for amount in ["0", "-1", "1e9", "0.0000000000000000001"] {
+        let (status, body) = app
+            .post(
+                "/v1/transfer",
+                json!({
+                    "from": { "chain_id": CHAIN_ID },
+                    "to": { "chain_id": OTHER_CHAIN_ID },
+                    "amount": amount
+                }),
+            )
+            .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", amount);
+        assert_eq!(body["code"], "invalid_amount");
+    }
+    assert!(app.linera.calls().is_empty());
+}
+
+#[tokio::test]
+async fn failed_transfer_reports_cli_error() {
+    let app = TestApp::new(FakeLinera::new().on(
+        "transfer",
+        CliOutput::failed(1, "Error: insufficient balance"),
+    ))
+    .with_wallet();
+
+    let (status, body) = app
+        .post(
+            "/v1/transfer",
+            json!({
+                "from": { "chain_id": CHAIN_ID },
+                "to": { "chain_id": OTHER_CHAIN_ID },
+                "amount": "1000"
+            }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["code"], "command_failed");
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("insufficient balance"));
+}
+
+#[tokio::test]
+async fn list_chains_returns_every_wallet_chain() {
+    let app =
+        TestApp::new(FakeLinera::new().on("wallet show", CliOutput::ok(WALLET_SHOW))).with_wallet();
+
+    let (status, body) = app.get("/v1/chains").await;
+
+    assert_eq!(status, StatusCode::OK);
+    let chains = body["data"].as_array().unwrap();
+    assert_eq!(chains.len(), 3);
+    assert_eq!(chains[1]["chain_id"], CHAIN_ID);
+    assert_eq!(chains[1]["is_default"], true);
+}
+
+#[tokio::test]
+async fn open_chain_returns_new_chain_and_message() {
+    let message_id = format!("{}:3:0", CHAIN_ID);
+    let app = TestApp::new(FakeLinera::new().on(
+        "open-chain",
+        CliOutput::ok(&format!("{}\n{}\n", message_id, OTHER_CHAIN_ID)),
+    ))
+    .with_wallet();
+
+    let (status, body) = app
+        .post(
+            "/v1/chains/open",
+            json!({ "from": CHAIN_ID, "owner": OWNER, "initial_balance": "5" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["data"],
+        json!({ "chain_id": OTHER_CHAIN_ID, "message_id": message_id })
+    );
+    assert_eq!(
+        app.linera.calls(),
+        [args(&format!(
+            "open-chain --from {} --owner {} --initial-balance 5",
+            CHAIN_ID, OWNER
+        ))]
+    );
+}
+
+#[tokio::test]
+async fn open_multi_owner_chain_passes_owners_and_weights() {
+    let second = "0x71C7656EC7ab88b098defB751B7401B5f6d8976F";
+    let app = TestApp::new(FakeLinera::new().on(
+        "open-multi-owner-chain",
+        CliOutput::ok(&format!("{}\n", OTHER_CHAIN_ID)),
+    ))
+    .with_wallet();
+
+    let (status, body) = app
+        .post(
+            "/v1/chains/open-multi-owner",
+            json!({ "owners": [OWNER, second], "owner_weights": [2, 1] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["chain_id"], OTHER_CHAIN_ID);
+    assert_eq!(body["data"]["message_id"], json!(null));
+    assert_eq!(
+        app.linera.calls(),
+        [args(&format!(
+            "open-multi-owner-chain --owners {} --owners {} --owner-weights 2 --owner-weights 1",
+            OWNER, second
+        ))]
+    );
+}
+
+#[tokio::test]
+async fn open_multi_owner_chain_checks_weights_match_owners() {
+    let app = TestApp::new(FakeLinera::new()).with_wallet();
+
+    let (status, body) = app
+        .post(
+            "/v1/chains/open-multi-owner",
+            json!({ "owners": [OWNER], "owner_weights": [1, 2] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_request");
+    assert!(app.linera.calls().is_empty());
+}
+
+#[tokio::test]
+async fn close_chain_runs_close_chain() {
+    let app = TestApp::new(FakeLinera::new().on("close-chain", CliOutput::ok(""))).with_wallet();
+
+    let (status, body) = app
+        .post(&format!("/v1/chains/{}/close", OTHER_CHAIN_ID), json!({}))
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["data"],
+        json!({ "status": "closed", "chain_id": OTHER_CHAIN_ID })
+    );
+    assert_eq!(
+        app.linera.calls(),
+        [args(&format!("close-chain {}", OTHER_CHAIN_ID))]
+    );
+}
@@ -0,0 +1,85 @@
+use axum::http::StatusCode;
+use linera_rest_server::client::{Client, ClientError};
+use linera_rest_server::models::{ErrorCode, ServiceState, ShutdownSignal, StartServiceRequest};
+
+use super::{free_port, TestApp, ADMIN_TOKEN, READ_TOKEN};
+use crate::linera_cli::CliOutput;
+use crate::testing::{FakeLinera, FakeService};
+
+const WALLET_SHOW: &str = include_str!("../../tests/fixtures/wallet_show_multi.txt");
+
+#[tokio::test]
+async fn client_decodes_typed_responses() {
+    let app = TestApp::new(
+        FakeLinera::new()
+            .on("wallet show", CliOutput::ok(WALLET_SHOW))
+            .service(FakeService::ready()),
+    )
+    .with_wallet();
+    let client = Client::new(app.serve().await).with_token(ADMIN_TOKEN);
+
+    client.health().await.unwrap();
+
+    let wallet = client.wallet_info().await.unwrap();
+    assert_eq!(wallet.chains.len(), 3);
+    assert!(wallet.chains[1].is_default);
+
+    let port = free_port();
+    let started = client
+        .start_service(&StartServiceRequest {
+            port: Some(port),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(started.port, port);
+    assert_eq!(started.storage, "memory");
+
+    let status = client.service_status().await.unwrap();
+    assert_eq!(status.state, ServiceState::Running);
+    assert_eq!(status.port, Some(port));
+
+    let stopped = client.stop_service(&Default::default()).await.unwrap();
+    assert_eq!(stopped.signal, Some(ShutdownSignal::Sigterm));
+
+    let instances = client.list_instances().await.unwrap();
+    assert_eq!(instances[0].name, "default");
+}
+
+#[tokio::test]
+async fn client_errors_carry_status_and_code() {
+    let app = TestApp::new(FakeLinera::new());
+    let base = app.serve().await;
+
+    let err = Client::new(&base)
+        .with_token(ADMIN_TOKEN)
+        .wallet_info()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Api {
+            status: StatusCode::NOT_FOUND,
+            code: ErrorCode::WalletNotInitialized,
+            ..
+        }
+    ));
+
+    let err = Client::new(&base).service_status().await.unwrap_err();
+    assert_eq!(err.code(), Some(ErrorCode::Unauthorized));
+
+    let err = Client::new(&base)
+        .with_token(READ_TOKEN)
+        .keygen()
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some(ErrorCode::Forbidden));
+
+    let err = Client::new(&base)
+        .with_token(ADMIN_TOKEN)
+        .instance("nope")
+        .service_status()
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some(ErrorCode::InstanceNotFound));
+}
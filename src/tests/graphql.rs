@@ -0,0 +1,189 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::{free_port, TestApp};
+use crate::testing::{FakeLinera, FakeService, MockGraphQL, MockResponse};
+
+const CHAIN_ID: &str = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4";
+const APP_ID: &str = "e476187f6ddfeb9d588c7b45d3df334d5501d6499b3f9ad5595cae86cce16a65";
+
+/// An app whose linera service is running and answers with `mock`
+async fn running(mock: &MockGraphQL) -> TestApp {
+    let app = TestApp::new(FakeLinera::new().service(FakeService::serving(mock.router())));
+
+    let (status, body) = app
+        .post("/v1/service/start", json!({ "port": free_port() }))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    app
+}
+
+#[tokio::test]
+async fn chain_query_forwards_query_and_variables() {
+    let mock = MockGraphQL::new().respond(
+        &format!("/chains/{}", CHAIN_ID),
+        MockResponse::json(json!({
+            "data": { "chain": { "tipState": { "nextBlockHeight": 7 } } }
+        })),
+    );
+    let app = running(&mock).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/graphql",
+            json!({
+                "chain_id": CHAIN_ID,
+                "query": "query($id: ChainId!) { chain(chainId: $id) { tipState { nextBlockHeight } } }",
+                "variables": { "id": CHAIN_ID }
+            }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["chain"]["tipState"]["nextBlockHeight"], 7);
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, format!("/chains/{}", CHAIN_ID));
+    assert!(requests[0].body["query"]
+        .as_str()
+        .unwrap()
+        .starts_with("query($id"));
+    assert_eq!(requests[0].body["variables"], json!({ "id": CHAIN_ID }));
+}
+
+#[tokio::test]
+async fn application_query_goes_to_application_endpoint() {
+    let path = format!("/chains/{}/applications/{}", CHAIN_ID, APP_ID);
+    let mock = MockGraphQL::new().respond(
+        &path,
+        MockResponse::json(json!({ "data": { "value": 42 } })),
+    );
+    let app = running(&mock).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/graphql",
+            json!({ "chain_id": CHAIN_ID, "app_id": APP_ID, "query": "{ value }" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["value"], 42);
+    assert_eq!(mock.requests()[0].path, path);
+    // No variables were given, so none are forwarded
+    assert!(mock.requests()[0].body.get("variables").is_none());
+}
+
+#[tokio::test]
+async fn application_id_must_be_hex() {
+    let mock = MockGraphQL::new();
+    let app = running(&mock).await;
+
+    for app_id in ["counter", "../../system", &APP_ID[1..]] {
+        let (status, body) = app
+            .post(
+                "/v1/graphql",
+                json!({ "chain_id": CHAIN_ID, "app_id": app_id, "query": "{ value }" }),
+            )
+            .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", app_id);
+        assert_eq!(body["code"], "invalid_request");
+    }
+    assert!(mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn system_query_goes_to_root() {
+    let mock = MockGraphQL::new().respond(
+        "/",
+        MockResponse::json(json!({ "data": { "chains": { "list": [CHAIN_ID] } } })),
+    );
+    let app = running(&mock).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/graphql/system",
+            json!({ "query": "{ chains { list } }", "variables": { "unused": true } }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["chains"]["list"], json!([CHAIN_ID]));
+    assert_eq!(mock.requests()[0].path, "/");
+    assert_eq!(
+        mock.requests()[0].body["variables"],
+        json!({ "unused": true })
+    );
+}
+
+#[tokio::test]
+async fn graphql_errors_are_passed_through() {
+    let errors = json!({ "data": null, "errors": [{ "message": "Unknown field \"nope\"" }] });
+    let mock = MockGraphQL::new().respond(
+        &format!("/chains/{}", CHAIN_ID),
+        MockResponse::json(errors.clone()),
+    );
+    let app = running(&mock).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/graphql",
+            json!({ "chain_id": CHAIN_ID, "query": "{ nope }" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, errors);
+}
+
+#[tokio::test]
+async fn service_failure_is_bad_gateway() {
+    let mock = MockGraphQL::new().respond(
+        &format!("/chains/{}", CHAIN_ID),
+        MockResponse::status(StatusCode::INTERNAL_SERVER_ERROR, "storage error"),
+    );
+    let app = running(&mock).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/graphql",
+            json!({ "chain_id": CHAIN_ID, "query": "{ version }" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["code"], "graphql_error");
+    assert!(body["error"].as_str().unwrap().contains("storage error"));
+}
+
+#[tokio::test]
+async fn non_json_answer_is_bad_gateway() {
+    let mock = MockGraphQL::new().respond("/", MockResponse::status(StatusCode::OK, "<html>"));
+    let app = running(&mock).await;
+
+    let (status, body) = app
+        .post("/v1/graphql/system", json!({ "query": "{ version }" }))
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["code"], "graphql_error");
+}
+
+#[tokio::test]
+async fn invalid_chain_id_never_reaches_service() {
+    let mock = MockGraphQL::new();
+    let app = running(&mock).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/graphql",
+            json!({ "chain_id": "../admin", "query": "{ version }" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_chain_id");
+    assert!(mock.requests().is_empty());
+}
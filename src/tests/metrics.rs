@@ -0,0 +1,100 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{Method, Request, StatusCode},
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+use super::{free_port, TestApp, READ_TOKEN};
+use crate::linera_cli::CliOutput;
+use crate::testing::{FakeLinera, FakeService, MockGraphQL, MockResponse};
+
+const CHAIN_ID: &str = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4";
+
+async fn scrape(app: &TestApp) -> String {
+    let request = Request::get("/metrics")
+        .header("authorization", format!("Bearer {}", READ_TOKEN))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn metrics_need_a_token() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app.request_as(None, Method::GET, "/metrics", None).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "unauthorized");
+}
+
+#[tokio::test]
+async fn requests_and_commands_are_counted() {
+    let app = TestApp::new(FakeLinera::new().on("keygen", CliOutput::ok("0xabc\n")));
+
+    app.get("/v1/service/status").await;
+    app.get("/v1/service/status").await;
+    app.post("/v1/wallet/keygen", json!({})).await;
+
+    let metrics = scrape(&app).await;
+    assert!(
+        metrics.contains(
+            r#"http_requests_total{method="GET",route="/v1/service/status",status="200"} 2"#
+        ),
+        "{}",
+        metrics
+    );
+    assert!(metrics.contains(
+        r#"http_request_duration_seconds_count{method="POST",route="/v1/wallet/keygen"} 1"#
+    ));
+    assert!(
+        metrics.contains(r#"linera_cli_invocations_total{command="keygen",outcome="success"} 1"#)
+    );
+    assert!(metrics.contains(r#"linera_cli_duration_seconds_count{command="keygen"} 1"#));
+    assert!(metrics.contains(r#"linera_service_up{instance="default"} 0"#));
+}
+
+#[tokio::test]
+async fn service_and_graphql_failures_are_reported() {
+    let mock = MockGraphQL::new().respond(
+        &format!("/chains/{}", CHAIN_ID),
+        MockResponse::status(StatusCode::INTERNAL_SERVER_ERROR, "storage error"),
+    );
+    let app = TestApp::new(FakeLinera::new().service(FakeService::serving(mock.router())));
+    let (status, _) = app
+        .post("/v1/service/start", json!({ "port": free_port() }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .post(
+            "/v1/graphql",
+            json!({ "chain_id": CHAIN_ID, "query": "{ version }" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+
+    let metrics = scrape(&app).await;
+    assert!(
+        metrics.contains(&format!(
+            r#"graphql_upstream_errors_total{{app="",chain="{}",kind="graphql_error"}} 1"#,
+            CHAIN_ID
+        )),
+        "{}",
+        metrics
+    );
+    assert!(metrics.contains(&format!(
+        r#"graphql_upstream_duration_seconds_count{{app="",chain="{}"}} 1"#,
+        CHAIN_ID
+    )));
+    assert!(metrics.contains(r#"linera_service_up{instance="default"} 1"#));
+    assert!(
+        metrics.contains(r#"linera_cli_invocations_total{command="service",outcome="success"} 1"#)
+    );
+
+    app.post("/v1/service/stop", json!({})).await;
+}
@@ -0,0 +1,161 @@
+//! End-to-end tests of the HTTP API against a scripted linera binary
+
+mod auth;
+mod chains;
+mod client;
+mod graphql;
+mod metrics;
+mod openapi;
+mod owners;
+mod service;
+mod versions;
+mod wallet;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use crate::auth::{ApiToken, Auth, Scope};
+use crate::config::{Config, LineraConfig};
+use crate::instances::{Instance, DEFAULT_INSTANCE};
+use crate::linera_manager::LineraManager;
+use crate::testing::FakeLinera;
+use crate::AppState;
+
+/// Admin token every request sends unless a test picks another one
+const ADMIN_TOKEN: &str = "admin-token";
+const READ_TOKEN: &str = "read-token";
+
+/// The router wired to a [`FakeLinera`], with wallet and state files in a temporary directory
+struct TestApp {
+    app: Router,
+    state: Arc<AppState>,
+    linera: Arc<FakeLinera>,
+    dir: TempDir,
+}
+
+/// Settings of an instance whose wallet, keystore and state files live in `dir`
+fn linera_config(dir: &Path) -> LineraConfig {
+    LineraConfig {
+        wallet: dir.join("wallet.json"),
+        keystore: dir.join("keystore.json"),
+        state_file: dir.join("state.json"),
+        ..LineraConfig::default()
+    }
+}
+
+impl TestApp {
+    fn new(linera: FakeLinera) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let linera = Arc::new(linera);
+
+        let manager = LineraManager::from_config(&linera_config(dir.path()), linera.clone());
+
+        let auth = Auth::new(vec![
+            ApiToken {
+                name: "admin".to_string(),
+                token: ADMIN_TOKEN.to_string(),
+                scope: Scope::Admin,
+            },
+            ApiToken {
+                name: "dashboard".to_string(),
+                token: READ_TOKEN.to_string(),
+                scope: Scope::Read,
+            },
+        ]);
+        let state = Arc::new(AppState::with_default_instance(
+            Instance::new(DEFAULT_INSTANCE, manager),
+            auth,
+            dir.path().join("instances.json"),
+        ));
+        let config = Config {
+            web_dir: dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        let app = crate::app(state.clone(), &config);
+
+        Self {
+            app,
+            state,
+            linera,
+            dir,
+        }
+    }
+
+    /// Pretend `wallet init` has already been run
+    fn with_wallet(self) -> Self {
+        std::fs::write(self.wallet_path(), "{}").unwrap();
+        self
+    }
+
+    /// Serve the router on an ephemeral port and return its base URL
+    async fn serve(&self) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = self.app.clone();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    fn wallet_path(&self) -> PathBuf {
+        self.dir.path().join("wallet.json")
+    }
+
+    async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        self.request(Method::GET, uri, None).await
+    }
+
+    async fn post(&self, uri: &str, body: Value) -> (StatusCode, Value) {
+        self.request(Method::POST, uri, Some(body)).await
+    }
+
+    async fn request(&self, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        self.request_as(Some(ADMIN_TOKEN), method, uri, body).await
+    }
+
+    async fn request_as(
+        &self,
+        token: Option<&str>,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, json)
+    }
+}
+
+/// A port nothing is listening on
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn args(command: &str) -> Vec<String> {
+    command.split_whitespace().map(String::from).collect()
+}
@@ -0,0 +1,66 @@
+use axum::http::{Method, StatusCode};
+
+use super::TestApp;
+use crate::testing::FakeLinera;
+
+#[tokio::test]
+async fn openapi_document_is_public() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, spec) = app
+        .request_as(None, Method::GET, "/v1/openapi.json", None)
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(spec["openapi"], "3.1.0");
+    assert!(spec["components"]["securitySchemes"]["bearer"].is_object());
+}
+
+#[tokio::test]
+async fn openapi_document_covers_the_api() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (_, spec) = app.get("/v1/openapi.json").await;
+
+    for (path, method) in [
+        ("/v1/health", "get"),
+        ("/v1/service/start", "post"),
+        ("/v1/service/logs/ws", "get"),
+        ("/v1/wallet/info", "get"),
+        ("/v1/owner/add", "post"),
+        ("/v1/graphql/system", "post"),
+        ("/v1/instances/{name}", "delete"),
+    ] {
+        assert!(
+            spec["paths"][path][method].is_object(),
+            "{} {} is not documented",
+            method,
+            path
+        );
+    }
+    assert!(spec["paths"]["/v1/health"]["get"]["security"].is_null());
+    assert!(spec["paths"]["/v1/wallet/info"]["get"]["responses"]["401"].is_object());
+
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["ServiceStarted"]["properties"]["status"]["enum"][0],
+        "started"
+    );
+    assert!(schemas["ErrorCode"]["enum"]
+        .as_array()
+        .unwrap()
+        .contains(&"linera_binary_missing".into()));
+}
+
+#[tokio::test]
+async fn transfer_certificate_hash_is_optional() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (_, spec) = app.get("/v1/openapi.json").await;
+
+    let schema = &spec["components"]["schemas"]["TransferResult"];
+    assert!(!schema["required"]
+        .as_array()
+        .unwrap()
+        .contains(&"certificate_hash".into()));
+}
@@ -0,0 +1,303 @@
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::{args, free_port, TestApp};
+use crate::linera_cli::CliOutput;
+use crate::testing::{FakeLinera, FakeService, MockGraphQL, MockResponse};
+
+const CHAIN_ID: &str = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4";
+const ALICE: &str = "0x3a6ba9e1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5";
+const BOB: &str = "0x7c0ffee1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5";
+const CAROL: &str = "0xc0ffee254729296a45a3885639ac7e10f9d54979";
+const DAVE: &str = "0xda7e000000000000000000000000000000000000";
+
+/// A service that reports `ownership` for every chain
+fn mock(ownership: Value) -> MockGraphQL {
+    MockGraphQL::new().respond(
+        "/",
+        MockResponse::json(json!({
+            "data": { "chain": { "executionState": { "system": { "ownership": ownership } } } }
+        })),
+    )
+}
+
+/// Alice as super owner and Bob as owner
+fn alice_and_bob() -> MockGraphQL {
+    mock(json!({
+        "super_owners": [ALICE],
+        "owners": { BOB: 100 },
+        "multi_leader_rounds": 10,
+        "open_multi_leader_rounds": false
+    }))
+}
+
+/// An app with a wallet whose linera service is running and answers with `mock`
+async fn running(mock: &MockGraphQL) -> TestApp {
+    let app = TestApp::new(
+        FakeLinera::new()
+            .on("change-ownership", CliOutput::ok(""))
+            .service(FakeService::serving(mock.router())),
+    )
+    .with_wallet();
+
+    let (status, body) = app
+        .post("/v1/service/start", json!({ "port": free_port() }))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    app
+}
+
+/// Commands run after `linera service`
+fn cli_calls(app: &TestApp) -> Vec<Vec<String>> {
+    app.linera.calls().split_off(1)
+}
+
+#[tokio::test]
+async fn owners_are_read_from_the_service() {
+    let mock = alice_and_bob();
+    let app = running(&mock).await;
+
+    let (status, body) = app.get(&format!("/v1/chains/{}/owners", CHAIN_ID)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["chain_id"], CHAIN_ID);
+    assert_eq!(body["data"]["super_owners"], json!([ALICE]));
+    assert_eq!(
+        body["data"]["owners"],
+        json!([{ "owner": BOB, "weight": 100 }])
+    );
+    assert_eq!(body["data"]["multi_leader_rounds"], 10);
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].path, "/");
+    assert_eq!(
+        requests[0].body["variables"],
+        json!({ "chainId": CHAIN_ID })
+    );
+}
+
+#[tokio::test]
+async fn owners_need_running_service() {
+    let app = TestApp::new(FakeLinera::new()).with_wallet();
+
+    let (status, body) = app.get(&format!("/v1/chains/{}/owners", CHAIN_ID)).await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "service_not_running");
+}
+
+#[tokio::test]
+async fn add_owner_keeps_current_owners() {
+    let app = running(&alice_and_bob()).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/add",
+            json!({ "chain_id": CHAIN_ID, "public_keys": [CAROL], "weight": 50 }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["owners"], json!([CAROL]));
+    assert_eq!(
+        body["data"]["ownership"]["owners"],
+        json!([{ "owner": BOB, "weight": 100 }, { "owner": CAROL, "weight": 50 }])
+    );
+    assert_eq!(
+        cli_calls(&app),
+        [args(&format!(
+            "change-ownership --chain-id {} --super-owners {} --owners {} --owners {} \
+             --owner-weights 100 --owner-weights 50 --multi-leader-rounds 10",
+            CHAIN_ID, ALICE, BOB, CAROL
+        ))]
+    );
+}
+
+#[tokio::test]
+async fn owner_changes_resubmit_timeouts() {
+    let app = running(&mock(json!({
+        "super_owners": [],
+        "owners": { BOB: 100 },
+        "multi_leader_rounds": 3,
+        "open_multi_leader_rounds": true,
+        "timeout_config": {
+            "fast_round_duration": 5_000_000,
+            "base_timeout": 20_000_000,
+            "timeout_increment": 2_000_000,
+            "fallback_duration": 86_400_000_000u64
+        }
+    })))
+    .await;
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/add",
+            json!({ "chain_id": CHAIN_ID, "public_keys": [CAROL] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["data"]["ownership"]["timeout_config"]["base_timeout_ms"],
+        20_000
+    );
+    assert_eq!(
+        cli_calls(&app),
+        [args(&format!(
+            "change-ownership --chain-id {} --owners {} --owners {} \
+             --owner-weights 100 --owner-weights 100 --multi-leader-rounds 3 \
+             --open-multi-leader-rounds --fast-round-ms 5000 --base-timeout-ms 20000 \
+             --timeout-increment-ms 2000 --fallback-duration-ms 86400000",
+            CHAIN_ID, BOB, CAROL
+        ))]
+    );
+}
+
+#[tokio::test]
+async fn concurrent_adds_keep_each_others_owners() {
+    // The service answers slowly and reports what the last change-ownership set
+    let ownership = |owners: Value| {
+        MockResponse::json(json!({
+            "data": { "chain": { "executionState": { "system": { "ownership": {
+                "owners": owners, "multi_leader_rounds": 10
+            } } } } }
+        }))
+        .delay(Duration::from_millis(100))
+    };
+    let mock = MockGraphQL::new().respond("/", ownership(json!({ BOB: 100 })));
+    let service = mock.clone();
+    let app = TestApp::new(
+        FakeLinera::new()
+            .on_call("change-ownership", move |args| {
+                let owners: serde_json::Map<_, _> = args
+                    .windows(2)
+                    .filter(|pair| pair[0] == "--owners")
+                    .map(|pair| (pair[1].clone(), json!(100)))
+                    .collect();
+                service.clone().respond("/", ownership(owners.into()));
+                CliOutput::ok("")
+            })
+            .service(FakeService::serving(mock.router())),
+    )
+    .with_wallet();
+    let (status, _) = app
+        .post("/v1/service/start", json!({ "port": free_port() }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let add = |owner: &str| {
+        app.post(
+            "/v1/owner/add",
+            json!({ "chain_id": CHAIN_ID, "public_keys": [owner] }),
+        )
+    };
+    let ((first, _), (second, _)) = tokio::join!(add(CAROL), add(DAVE));
+
+    assert_eq!((first, second), (StatusCode::OK, StatusCode::OK));
+    let (_, body) = app.get(&format!("/v1/chains/{}/owners", CHAIN_ID)).await;
+    let owners: Vec<_> = body["data"]["owners"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["owner"].as_str().unwrap())
+        .collect();
+    assert_eq!(owners.len(), 3, "{:?}", owners);
+    assert!(owners.contains(&CAROL) && owners.contains(&DAVE));
+}
+
+#[tokio::test]
+async fn add_existing_owner_changes_nothing() {
+    let app = running(&alice_and_bob()).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/add",
+            json!({ "chain_id": CHAIN_ID, "public_keys": [ALICE] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["data"]["ownership"]["owners"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(cli_calls(&app).is_empty());
+}
+
+#[tokio::test]
+async fn add_owner_rejects_invalid_input() {
+    let app = TestApp::new(FakeLinera::new()).with_wallet();
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/add",
+            json!({ "chain_id": "nope", "public_keys": [CAROL] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_chain_id");
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/add",
+            json!({ "chain_id": CHAIN_ID, "public_keys": ["0xaa"] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_owner");
+    assert!(app.linera.calls().is_empty());
+}
+
+#[tokio::test]
+async fn remove_owner_keeps_the_others() {
+    let app = running(&alice_and_bob()).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/remove",
+            json!({ "chain_id": CHAIN_ID, "owners": [ALICE] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["super_owners"], json!([]));
+    assert_eq!(
+        cli_calls(&app),
+        [args(&format!(
+            "change-ownership --chain-id {} --owners {} --owner-weights 100 --multi-leader-rounds 10",
+            CHAIN_ID, BOB
+        ))]
+    );
+}
+
+#[tokio::test]
+async fn remove_owner_refuses_to_lock_everyone_out() {
+    let app = running(&alice_and_bob()).await;
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/remove",
+            json!({ "chain_id": CHAIN_ID, "owners": [ALICE, BOB] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("without owners"));
+
+    let (status, body) = app
+        .post(
+            "/v1/owner/remove",
+            json!({ "chain_id": CHAIN_ID, "owners": [CAROL] }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("not an owner"));
+    assert!(cli_calls(&app).is_empty());
+}
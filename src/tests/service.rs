@@ -0,0 +1,453 @@
+use axum::http::{Method, StatusCode};
+use axum::{routing::get, Router};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Child;
+
+use super::{args, free_port, linera_config, TestApp};
+use crate::config::LineraConfig;
+use crate::linera_manager::{LineraManager, OrphanPolicy};
+use crate::models::{ServiceState, ShutdownSignal};
+use crate::service_process::{cmdline, is_alive};
+use crate::testing::{FakeLinera, FakeService};
+
+#[tokio::test]
+async fn start_status_logs_and_stop() {
+    let app = TestApp::new(
+        FakeLinera::new().service(FakeService::ready().stderr("INFO linera_service: ready")),
+    );
+    let port = free_port();
+
+    let (status, body) = app.post("/v1/service/start", json!({ "port": port })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["storage"], "memory");
+    assert_eq!(
+        app.linera.calls(),
+        [args(&format!("service --port {}", port))]
+    );
+
+    let (_, body) = app.get("/v1/service/status").await;
+    assert_eq!(body["data"]["state"], "running");
+    assert_eq!(body["data"]["port"], port);
+
+    let mut logs = json!([]);
+    for _ in 0..20 {
+        logs = app.get("/v1/service/logs").await.1["data"].clone();
+        if !logs.as_array().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(logs[0]["line"], "INFO linera_service: ready");
+    assert_eq!(logs[0]["level"], "info");
+
+    let (status, body) = app.post("/v1/service/start", json!({ "port": port })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "service_already_running");
+
+    let (status, body) = app.post("/v1/service/stop", json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["signal"], "sigterm");
+
+    let (status, body) = app.post("/v1/service/stop", json!({})).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "service_not_running");
+}
+
+#[tokio::test]
+async fn stop_rejects_malformed_body() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app
+        .post("/v1/service/stop", json!({ "grace_period_secs": "ten" }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_request");
+
+    // An empty body is fine and reaches the manager
+    let (status, body) = app.request(Method::POST, "/v1/service/stop", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "service_not_running");
+}
+
+#[tokio::test]
+async fn start_reports_early_exit_with_stderr() {
+    let app = TestApp::new(
+        FakeLinera::new().service(
+            FakeService::exits(3)
+                .stdout("Opening storage")
+                .stderr("Error: storage is locked"),
+        ),
+    );
+
+    let (status, body) = app
+        .post("/v1/service/start", json!({ "port": free_port() }))
+        .await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["code"], "service_start_failed");
+    assert_eq!(body["data"]["exit_code"], 3);
+    assert_eq!(
+        body["data"]["stderr_tail"],
+        json!(["Error: storage is locked"])
+    );
+
+    let (_, body) = app.get("/v1/service/status").await;
+    assert_eq!(body["data"]["state"], "crashed");
+}
+
+#[tokio::test]
+async fn start_times_out_when_never_ready() {
+    let app = TestApp::new(FakeLinera::new().service(FakeService::hangs()));
+
+    let (status, body) = app
+        .post(
+            "/v1/service/start",
+            json!({ "port": free_port(), "startup_timeout_secs": 1 }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["data"]["reason"], "not ready after 1 seconds");
+}
+
+#[tokio::test]
+async fn start_rejects_invalid_storage() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app
+        .post(
+            "/v1/service/start",
+            json!({ "port": free_port(), "storage": "rocksdb:/nonexistent/db" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_storage");
+    assert!(app.linera.calls().is_empty());
+}
+
+#[tokio::test]
+async fn graphql_needs_running_service() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app
+        .post(
+            "/v1/graphql/system",
+            json!({ "query": "{ chains { list } }" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "service_not_running");
+}
+
+#[tokio::test]
+async fn unknown_instance_is_not_found() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app.get("/v1/instances/nope/service/status").await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "instance_not_found");
+    assert!(app.state.instances.get("nope").await.is_none());
+}
+
+#[tokio::test]
+async fn instances_start_on_their_own_port() {
+    let app = TestApp::new(FakeLinera::new().service(FakeService::ready()));
+    let port = free_port();
+
+    let (status, body) = app
+        .post(
+            "/v1/instances",
+            json!({ "name": "alice", "service_port": port }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    assert_eq!(body["data"]["service_port"], port);
+
+    let (status, body) = app
+        .post(
+            "/v1/instances",
+            json!({ "name": "bob", "service_port": port }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "port_in_use");
+
+    let (status, body) = app
+        .post("/v1/instances/alice/service/start", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["port"], port);
+
+    let (status, _) = app
+        .post("/v1/instances/alice/service/stop", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn removing_instance_forgets_its_service() {
+    let app = TestApp::new(FakeLinera::new());
+    let (status, _) = app.post("/v1/instances", json!({ "name": "alice" })).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // A record left behind by a service that died while the server was down
+    let state_file = app.dir.path().join("state.json.alice");
+    std::fs::write(&state_file, "{}").unwrap();
+
+    let (status, _) = app
+        .request(Method::DELETE, "/v1/instances/alice", None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!state_file.exists());
+}
+
+/// Poll the default instance's status until `done` accepts it
+async fn wait_for_status(app: &TestApp, done: impl Fn(&Value) -> bool) -> Option<Value> {
+    for _ in 0..200 {
+        let (_, body) = app.get("/v1/service/status").await;
+        if done(&body["data"]) {
+            return Some(body["data"].clone());
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    None
+}
+
+async fn wait_for_state(app: &TestApp, state: &str) -> Value {
+    wait_for_status(app, |status| status["state"] == state)
+        .await
+        .unwrap_or_else(|| panic!("service never reached state {}", state))
+}
+
+/// SIGKILL the service's process, as a crash would end it
+fn kill(status: &Value) {
+    let pid = status["pid"].as_i64().expect("service has a pid");
+    // SAFETY: kill(2) has no memory-safety requirements
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+}
+
+#[tokio::test]
+async fn supervisor_restarts_crashed_service() {
+    let app = TestApp::new(FakeLinera::new().service(FakeService::ready()));
+    let restart = json!({ "mode": "on_failure", "initial_backoff_ms": 500 });
+
+    let (status, body) = app
+        .post(
+            "/v1/service/start",
+            json!({ "port": free_port(), "restart": restart }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let first = wait_for_state(&app, "running").await;
+    assert_eq!(first["restart_count"], 0);
+
+    kill(&first);
+    let backoff = wait_for_state(&app, "backoff").await;
+    assert_eq!(backoff["last_exit"]["signal"], libc::SIGKILL);
+    assert_eq!(backoff["last_exit"]["code"], Value::Null);
+
+    let second = wait_for_state(&app, "running").await;
+    assert_eq!(second["restart_count"], 1);
+    assert_ne!(second["pid"], first["pid"]);
+    assert_eq!(app.linera.calls().len(), 2);
+
+    let (status, _) = app.post("/v1/service/stop", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn supervisor_gives_up_after_max_restarts() {
+    let app = TestApp::new(FakeLinera::new().service(FakeService::ready()));
+    let restart = json!({ "mode": "always", "max_restarts": 1, "initial_backoff_ms": 50 });
+
+    let (status, body) = app
+        .post(
+            "/v1/service/start",
+            json!({ "port": free_port(), "restart": restart }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    kill(&wait_for_state(&app, "running").await);
+    let restarted = wait_for_status(&app, |status| {
+        status["state"] == "running" && status["restart_count"] == 1
+    })
+    .await
+    .expect("service was restarted");
+
+    kill(&restarted);
+    let crashed = wait_for_state(&app, "crashed").await;
+    assert_eq!(crashed["restart_count"], 1);
+    assert_eq!(crashed["pid"], Value::Null);
+    assert_eq!(app.linera.calls().len(), 2);
+
+    let (status, body) = app.post("/v1/service/stop", json!({})).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "service_not_running");
+}
+
+#[tokio::test]
+async fn stop_during_relaunch_cancels_restart() {
+    // Ready at first, then never again, so the relaunch waits for readiness
+    let ready = Arc::new(AtomicBool::new(true));
+    let probe = ready.clone();
+    let endpoint = Router::new().route(
+        "/",
+        get(move || async move {
+            if probe.load(Ordering::SeqCst) {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }),
+    );
+    let app = TestApp::new(FakeLinera::new().service(FakeService::serving(endpoint)));
+    let restart = json!({ "mode": "on_failure", "initial_backoff_ms": 50 });
+
+    let (status, body) = app
+        .post(
+            "/v1/service/start",
+            json!({ "port": free_port(), "restart": restart }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    ready.store(false, Ordering::SeqCst);
+    kill(&wait_for_state(&app, "running").await);
+    let starting = wait_for_state(&app, "starting").await;
+    assert_eq!(starting["restart_count"], 1);
+
+    let (status, body) = app.post("/v1/service/stop", json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["signal"], Value::Null);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let (_, body) = app.get("/v1/service/status").await;
+    assert_eq!(body["data"]["state"], "stopped");
+    assert_eq!(app.linera.calls().len(), 2);
+}
+
+/// A service left running by a previous server, recorded in `dir`'s state file, and a
+/// manager for `dir` that deals with it per `policy`
+async fn orphan(dir: &Path, policy: OrphanPolicy) -> (Child, u32, LineraManager) {
+    let port = free_port();
+    let child = FakeService::ready().spawn(Some(port)).unwrap();
+    let pid = child.id().unwrap();
+
+    // The record only matches once the shell has exec'd into the long-running process
+    let argv = ["sleep", "3600"];
+    for _ in 0..100 {
+        if cmdline(pid).is_some_and(|cmd| cmd == argv) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let record = json!({
+        "pid": pid,
+        "args": argv,
+        "config": {
+            "port": port,
+            "startup_timeout_secs": 30,
+            "restart": { "mode": "never" },
+            "storage": "memory"
+        },
+        "started_at_ms": 0
+    });
+    std::fs::write(dir.join("state.json"), record.to_string()).unwrap();
+
+    let config = LineraConfig {
+        orphan_policy: policy,
+        ..linera_config(dir)
+    };
+    let manager = LineraManager::from_config(&config, Arc::new(FakeLinera::new()));
+
+    (child, pid, manager)
+}
+
+#[tokio::test]
+async fn reconcile_adopts_orphaned_service() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_child, pid, manager) = orphan(dir.path(), OrphanPolicy::Adopt).await;
+
+    manager.reconcile().await;
+
+    let status = manager.status().await;
+    assert_eq!(status.state, ServiceState::Running);
+    assert!(status.running);
+    assert_eq!(status.pid, Some(pid));
+    assert_eq!(status.storage.as_deref(), Some("memory"));
+
+    let stopped = manager
+        .stop_service(Some(Duration::from_secs(5)))
+        .await
+        .unwrap();
+    assert_eq!(stopped.signal, Some(ShutdownSignal::Sigterm));
+    assert!(!is_alive(pid));
+    assert!(!dir.path().join("state.json").exists());
+}
+
+#[tokio::test]
+async fn reconcile_kills_orphaned_service() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_child, pid, manager) = orphan(dir.path(), OrphanPolicy::Kill).await;
+
+    manager.reconcile().await;
+
+    assert!(!is_alive(pid));
+    assert!(!dir.path().join("state.json").exists());
+    let status = manager.status().await;
+    assert!(!status.running);
+    assert_eq!(status.pid, None);
+}
+
+#[tokio::test]
+async fn stop_during_first_start_wins() {
+    // Not ready until the stop has been answered
+    let ready = Arc::new(AtomicBool::new(false));
+    let probe = ready.clone();
+    let endpoint = Router::new().route(
+        "/",
+        get(move || async move {
+            if probe.load(Ordering::SeqCst) {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }),
+    );
+    let app = Arc::new(TestApp::new(
+        FakeLinera::new().service(FakeService::serving(endpoint)),
+    ));
+
+    let starting = {
+        let app = app.clone();
+        tokio::spawn(async move {
+            app.post("/v1/service/start", json!({ "port": free_port() }))
+                .await
+        })
+    };
+    wait_for_state(&app, "starting").await;
+
+    let (status, body) = app.post("/v1/service/stop", json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    ready.store(true, Ordering::SeqCst);
+
+    let (status, body) = starting.await.unwrap();
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{}", body);
+    assert_eq!(body["data"]["reason"], "stopped while starting");
+
+    let (_, body) = app.get("/v1/service/status").await;
+    assert_eq!(body["data"]["state"], "stopped");
+    assert_eq!(body["data"]["pid"], Value::Null);
+    let pids = app.linera.service_pids();
+    assert_eq!(pids.len(), 1);
+    assert!(!is_alive(pids[0]));
+}
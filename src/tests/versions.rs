@@ -0,0 +1,68 @@
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request, StatusCode},
+};
+use tower::ServiceExt;
+
+use super::{TestApp, ADMIN_TOKEN};
+use crate::testing::FakeLinera;
+
+async fn headers(app: &TestApp, uri: &str) -> (StatusCode, HeaderMap) {
+    let request = Request::get(uri)
+        .header("authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    (response.status(), response.headers().clone())
+}
+
+#[tokio::test]
+async fn versioned_routes_are_not_deprecated() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, headers) = headers(&app, "/v1/service/status").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(headers.get("deprecation").is_none());
+}
+
+#[tokio::test]
+async fn unversioned_routes_are_deprecated_aliases() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (_, v1) = app.get("/v1/instances/default/service/status").await;
+    let (_, alias) = app.get("/instances/default/service/status").await;
+    assert_eq!(alias, v1);
+
+    let (status, headers) = headers(&app, "/instances/default/service/status").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(headers["deprecation"].to_str().unwrap().starts_with('@'));
+    assert_eq!(
+        headers["link"],
+        "</v1/instances/default/service/status>; rel=\"successor-version\""
+    );
+}
+
+#[tokio::test]
+async fn public_routes_are_not_deprecated() {
+    let app = TestApp::new(FakeLinera::new());
+
+    for uri in ["/health", "/v1/health", "/openapi.json", "/v1/openapi.json"] {
+        let (status, headers) = headers(&app, uri).await;
+        assert_eq!(status, StatusCode::OK, "{}", uri);
+        assert!(headers.get("deprecation").is_none(), "{}", uri);
+        assert!(headers.get("link").is_none(), "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn aliases_still_require_a_token() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app
+        .request_as(None, axum::http::Method::GET, "/wallet/info", None)
+        .await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "unauthorized");
+}
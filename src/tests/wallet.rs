@@ -0,0 +1,386 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+use super::{args, free_port, TestApp};
+use crate::linera_cli::CliOutput;
+use crate::testing::{FakeLinera, MockGraphQL};
+
+const WALLET_SHOW: &str = include_str!("../../tests/fixtures/wallet_show_multi.txt");
+const WALLET_SHOW_SINGLE: &str = include_str!("../../tests/fixtures/wallet_show_single.txt");
+const CHAIN_ID: &str = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4";
+const REQUESTED_CHAIN_ID: &str = "e476187f6ddfeb9d588c7b45d3df334d5501d6499b3f9ad5595cae86cce16a65";
+
+/// URL of a local stand-in for the faucet, which only needs to answer GET /
+async fn faucet() -> String {
+    format!("http://127.0.0.1:{}", MockGraphQL::new().start().await)
+}
+
+#[tokio::test]
+async fn wallet_info_lists_chains() {
+    let app =
+        TestApp::new(FakeLinera::new().on("wallet show", CliOutput::ok(WALLET_SHOW))).with_wallet();
+
+    let (status, body) = app.get("/v1/wallet/info").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["chain_id"], CHAIN_ID);
+    assert_eq!(body["data"]["chains"].as_array().unwrap().len(), 3);
+    assert_eq!(body["data"]["chains"][1]["is_default"], true);
+}
+
+#[tokio::test]
+async fn wallet_info_without_wallet_is_not_found() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app.get("/v1/wallet/info").await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "wallet_not_initialized");
+    assert!(app.linera.calls().is_empty());
+}
+
+#[tokio::test]
+async fn init_wallet_uses_faucet_then_shows_wallet() {
+    let faucet = faucet().await;
+    let app = TestApp::new(
+        FakeLinera::new()
+            .on("wallet init", CliOutput::ok(""))
+            .on("wallet show", CliOutput::ok(WALLET_SHOW)),
+    );
+
+    let (status, body) = app
+        .post("/v1/wallet/init", json!({ "faucet_url": faucet }))
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["chain_id"], CHAIN_ID);
+    assert_eq!(
+        app.linera.calls(),
+        [
+            args(&format!("wallet init --faucet {}", faucet)),
+            args("wallet show")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn init_wallet_refuses_existing_wallet() {
+    let app = TestApp::new(FakeLinera::new()).with_wallet();
+
+    let (status, body) = app
+        .post(
+            "/v1/wallet/init",
+            json!({ "faucet_url": "http://faucet.test" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "wallet_already_exists");
+}
+
+#[tokio::test]
+async fn init_wallet_reports_cli_failure() {
+    let app = TestApp::new(FakeLinera::new().on(
+        "wallet init",
+        CliOutput::failed(1, "Error: faucet unreachable"),
+    ));
+
+    let (status, body) = app
+        .post("/v1/wallet/init", json!({ "faucet_url": faucet().await }))
+        .await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["code"], "command_failed");
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("faucet unreachable"));
+}
+
+#[tokio::test]
+async fn init_wallet_checks_faucet_before_running_linera() {
+    let app = TestApp::new(FakeLinera::new());
+    let unreachable = format!("http://127.0.0.1:{}", free_port());
+
+    let (status, body) = app
+        .post("/v1/wallet/init", json!({ "faucet_url": unreachable }))
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["code"], "faucet_unreachable");
+
+    let (status, body) = app
+        .post("/v1/wallet/init", json!({ "faucet_url": "faucet.test" }))
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_request");
+    assert!(app.linera.calls().is_empty());
+}
+
+#[tokio::test]
+async fn request_chain_adds_chain_to_wallet() {
+    let faucet = faucet().await;
+    let app = TestApp::new(
+        FakeLinera::new()
+            .on(
+                "wallet request-chain",
+                CliOutput::ok(&format!(
+                    "{}\n0x7c0ffee1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5\n",
+                    REQUESTED_CHAIN_ID
+                )),
+            )
+            .on("wallet show", CliOutput::ok(WALLET_SHOW)),
+    )
+    .with_wallet();
+
+    let (status, body) = app
+        .post("/v1/wallet/request-chain", json!({ "faucet_url": faucet }))
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["chain_id"], REQUESTED_CHAIN_ID);
+    assert!(body["data"]["owner"]
+        .as_str()
+        .unwrap()
+        .starts_with("0x7c0ffee"));
+    assert_eq!(body["data"]["is_default"], false);
+    assert_eq!(
+        app.linera.calls(),
+        [
+            args(&format!("wallet request-chain --faucet {}", faucet)),
+            args("wallet show")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn request_chain_can_set_default() {
+    let faucet = faucet().await;
+    let app = TestApp::new(
+        FakeLinera::new()
+            .on(
+                "wallet request-chain",
+                CliOutput::ok(&format!("{}\n", CHAIN_ID)),
+            )
+            .on("wallet show", CliOutput::ok(WALLET_SHOW)),
+    )
+    .with_wallet();
+
+    let (status, body) = app
+        .post(
+            "/v1/wallet/request-chain",
+            json!({ "faucet_url": faucet, "set_default": true }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["is_default"], true);
+    assert_eq!(
+        app.linera.calls()[0],
+        args(&format!(
+            "wallet request-chain --faucet {} --set-default",
+            faucet
+        ))
+    );
+}
+
+#[tokio::test]
+async fn request_chain_needs_wallet_and_reachable_faucet() {
+    let app = TestApp::new(FakeLinera::new());
+
+    let (status, body) = app
+        .post(
+            "/v1/wallet/request-chain",
+            json!({ "faucet_url": faucet().await }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "wallet_not_initialized");
+
+    let app = app.with_wallet();
+    let unreachable = format!("http://127.0.0.1:{}", free_port());
+    let (status, body) = app
+        .post(
+            "/v1/wallet/request-chain",
+            json!({ "faucet_url": unreachable }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["code"], "faucet_unreachable");
+    assert!(app.linera.calls().is_empty());
+}
+
+/// `wallet show` output that follows the `set-default` and `forget-chain` commands run so far
+fn editable_wallet(linera: FakeLinera) -> FakeLinera {
+    let wallet = Arc::new(Mutex::new(WALLET_SHOW.to_string()));
+    let (set_default, forget) = (wallet.clone(), wallet.clone());
+
+    linera
+        .on_call("wallet show", move |_| {
+            CliOutput::ok(&wallet.lock().unwrap())
+        })
+        .on_call("wallet set-default", move |args| {
+            let mut wallet = set_default.lock().unwrap();
+            *wallet = with_default(&wallet, &args[2]);
+            CliOutput::ok("")
+        })
+        .on_call("wallet forget-chain", move |args| {
+            let mut wallet = forget.lock().unwrap();
+            *wallet = without_chain(&wallet, &args[2]);
+            CliOutput::ok("")
+        })
+}
+
+/// `output` with `chain_id` tagged as the default chain instead of the current one
+fn with_default(output: &str, chain_id: &str) -> String {
+    let mut edited = String::new();
+    for line in output.lines().filter(|line| !line.starts_with("Tags:")) {
+        edited.push_str(line);
+        edited.push('\n');
+        if line.starts_with("Chain ID:") && line.ends_with(chain_id) {
+            edited.push_str("Tags:                 DEFAULT\n");
+        }
+    }
+    edited
+}
+
+/// `output` without the block of `chain_id`
+fn without_chain(output: &str, chain_id: &str) -> String {
+    let mut skipping = false;
+    let mut edited = String::new();
+    for line in output.lines() {
+        if line.starts_with("Chain ID:") {
+            skipping = line.ends_with(chain_id);
+        } else if line.starts_with("---") {
+            skipping = false;
+        }
+        if !skipping {
+            edited.push_str(line);
+            edited.push('\n');
+        }
+    }
+    edited
+}
+
+#[tokio::test]
+async fn set_default_chain_checks_wallet_then_shows_it() {
+    let app = TestApp::new(editable_wallet(FakeLinera::new())).with_wallet();
+    let (_, before) = app.get("/v1/wallet/info").await;
+    assert_eq!(before["data"]["default_chain"], CHAIN_ID);
+
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            "/v1/wallet/default-chain",
+            Some(json!({ "chain_id": REQUESTED_CHAIN_ID })),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["default_chain"], REQUESTED_CHAIN_ID);
+    assert_eq!(body["data"]["chain_id"], REQUESTED_CHAIN_ID);
+    assert_eq!(
+        app.linera.calls()[1..],
+        [
+            args("wallet show"),
+            args(&format!("wallet set-default {}", REQUESTED_CHAIN_ID)),
+            args("wallet show")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn set_default_chain_rejects_unknown_chain() {
+    let app =
+        TestApp::new(FakeLinera::new().on("wallet show", CliOutput::ok(WALLET_SHOW))).with_wallet();
+    let unknown = "ab".repeat(32);
+
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            "/v1/wallet/default-chain",
+            Some(json!({ "chain_id": unknown })),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "chain_not_in_wallet");
+    assert_eq!(app.linera.calls(), [args("wallet show")]);
+}
+
+#[tokio::test]
+async fn forget_chain_runs_wallet_forget_chain() {
+    let app = TestApp::new(editable_wallet(FakeLinera::new())).with_wallet();
+
+    let (status, body) = app
+        .request(
+            Method::DELETE,
+            &format!("/v1/wallet/chains/{}", REQUESTED_CHAIN_ID),
+            None,
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let chains: Vec<_> = body["data"]["chains"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|chain| chain["chain_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(chains.len(), 2);
+    assert!(!chains.contains(&REQUESTED_CHAIN_ID));
+    assert_eq!(body["data"]["default_chain"], CHAIN_ID);
+    assert_eq!(
+        app.linera.calls()[1],
+        args(&format!("wallet forget-chain {}", REQUESTED_CHAIN_ID))
+    );
+}
+
+#[tokio::test]
+async fn forget_chain_keeps_the_only_chain() {
+    let app = TestApp::new(FakeLinera::new().on("wallet show", CliOutput::ok(WALLET_SHOW_SINGLE)))
+        .with_wallet();
+
+    let (status, body) = app
+        .request(
+            Method::DELETE,
+            &format!("/v1/wallet/chains/{}", CHAIN_ID),
+            None,
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_request");
+    assert_eq!(app.linera.calls(), [args("wallet show")]);
+
+    let (status, body) = app
+        .request(Method::DELETE, "/v1/wallet/chains/not-a-chain", None)
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_chain_id");
+}
+
+#[tokio::test]
+async fn keygen_returns_public_key() {
+    let app = TestApp::new(FakeLinera::new().on("keygen", CliOutput::ok("0xabc123\n")));
+
+    let (status, body) = app.post("/v1/wallet/keygen", json!({})).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["public_key"], "0xabc123");
+}
+
+#[tokio::test]
+async fn missing_binary_is_unavailable() {
+    let app = TestApp::new(FakeLinera::missing());
+
+    let (status, body) = app.post("/v1/wallet/keygen", json!({})).await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "linera_binary_missing");
+}
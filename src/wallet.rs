@@ -0,0 +1,296 @@
+use anyhow::{anyhow, Result};
+
+use crate::error::ApiError;
+use crate::models::{ChainOpened, WalletChain, WalletInfo};
+use crate::service_logs::strip_ansi;
+
+/// Shown in place of an owner for chains the wallet holds no key for
+const NO_OWNER: &str = "No owner key";
+
+/// Parse the output of `linera wallet show`.
+///
+/// Each chain is printed as a block of `Label:  value` lines starting with
+/// `Chain ID:`; blocks are separated by dashed lines and may be interleaved
+/// with log lines, which are ignored.
+pub fn parse_wallet_show(output: &str) -> Result<WalletInfo> {
+    let chains = parse_chains(output)?;
+
+    let default = chains
+        .iter()
+        .find(|c| c.is_default)
+        .or_else(|| chains.first())
+        .ok_or_else(|| anyhow!("Wallet has no chains"))?;
+
+    Ok(WalletInfo {
+        chain_id: default.chain_id.clone(),
+        public_key: default
+            .owner
+            .clone()
+            .unwrap_or_else(|| NO_OWNER.to_string()),
+        default_chain: chains
+            .iter()
+            .find(|c| c.is_default)
+            .map(|c| c.chain_id.clone()),
+        chains,
+    })
+}
+
+fn parse_chains(output: &str) -> Result<Vec<WalletChain>> {
+    let mut chains = Vec::new();
+    let mut current: Option<WalletChain> = None;
+
+    for line in output.lines() {
+        let line = strip_ansi(line);
+        let Some((label, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match label.trim() {
+            "Chain ID" => {
+                if !is_hex_id(value) {
+                    return Err(anyhow!("Invalid chain ID in wallet output: {}", value));
+                }
+                chains.extend(current.take());
+                current = Some(WalletChain {
+                    chain_id: value.to_string(),
+                    owner: None,
+                    block_height: 0,
+                    timestamp: None,
+                    epoch: None,
+                    latest_block_hash: None,
+                    is_default: false,
+                });
+            }
+            label => {
+                // Anything before the first chain is a log line or a header
+                let Some(chain) = current.as_mut() else {
+                    continue;
+                };
+                match label {
+                    "Tags" => chain.is_default = value.split(',').any(|t| t.trim() == "DEFAULT"),
+                    "Default owner" => chain.owner = present(value).filter(|v| *v != NO_OWNER),
+                    "Timestamp" => chain.timestamp = present(value),
+                    "Blocks" | "Next Block Height" => {
+                        chain.block_height = value.parse().map_err(|_| {
+                            anyhow!(
+                                "Invalid block height for chain {}: {}",
+                                chain.chain_id,
+                                value
+                            )
+                        })?;
+                    }
+                    "Epoch" => chain.epoch = present(value).and_then(|v| v.parse().ok()),
+                    "Latest block hash" | "Block Hash" => chain.latest_block_hash = present(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    chains.extend(current);
+    Ok(chains)
+}
+
+/// Parse the output of `linera open-chain` and `open-multi-owner-chain`.
+///
+/// The new chain ID is printed on its own line; older linera versions print the
+/// ID of the message that created it (`<chain>:<height>:<index>`) before it.
+pub fn parse_open_chain(output: &str) -> Result<ChainOpened> {
+    let mut chain_id = None;
+    let mut message_id = None;
+
+    for line in output.lines() {
+        let line = strip_ansi(line);
+        let line = line.trim();
+        if is_hex_id(line) {
+            chain_id = Some(line.to_string());
+        } else if is_message_id(line) {
+            message_id = Some(line.to_string());
+        }
+    }
+
+    Ok(ChainOpened {
+        chain_id: chain_id.ok_or_else(|| anyhow!("No chain ID in output: {}", output.trim()))?,
+        message_id,
+    })
+}
+
+/// Parse the output of `linera wallet request-chain`: the new chain ID, then the
+/// owner the wallet holds a key for on it
+pub fn parse_requested_chain(output: &str) -> Result<String> {
+    output
+        .lines()
+        .map(|line| strip_ansi(line).trim().to_string())
+        .find(|line| is_hex_id(line))
+        .ok_or_else(|| anyhow!("No chain ID in output: {}", output.trim()))
+}
+
+fn is_message_id(value: &str) -> bool {
+    let mut parts = value.split(':');
+    parts.next().is_some_and(is_hex_id)
+        && parts.clone().count() == 2
+        && parts.all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Reject chain IDs before they reach the CLI or a GraphQL URL
+pub fn validate_chain_id(chain_id: &str) -> Result<(), ApiError> {
+    if is_hex_id(chain_id) {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidChainId(chain_id.to_string()))
+    }
+}
+
+/// Application IDs are 64 hex characters, like chain IDs
+pub fn validate_application_id(app_id: &str) -> Result<(), ApiError> {
+    if is_hex_id(app_id) {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidRequest(format!(
+            "Invalid application ID {:?}: expected 64 hex characters",
+            app_id
+        )))
+    }
+}
+
+/// The CLI prints `-` for missing values
+fn present(value: &str) -> Option<String> {
+    (!value.is_empty() && value != "-").then(|| value.to_string())
+}
+
+fn is_hex_id(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINGLE: &str = include_str!("../tests/fixtures/wallet_show_single.txt");
+    const MULTI: &str = include_str!("../tests/fixtures/wallet_show_multi.txt");
+    const EMPTY: &str = include_str!("../tests/fixtures/wallet_show_empty.txt");
+
+    #[test]
+    fn parses_fresh_faucet_wallet() {
+        let wallet = parse_wallet_show(SINGLE).unwrap();
+
+        assert_eq!(wallet.chains.len(), 1);
+        assert_eq!(
+            wallet.chain_id,
+            "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4"
+        );
+        assert_eq!(
+            wallet.public_key,
+            "0x3a6ba9e1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5"
+        );
+
+        let chain = &wallet.chains[0];
+        assert!(chain.is_default);
+        assert_eq!(chain.block_height, 0);
+        assert_eq!(
+            chain.timestamp.as_deref(),
+            Some("2025-09-02 08:13:58.104512")
+        );
+        assert_eq!(chain.epoch, Some(0));
+        assert_eq!(chain.latest_block_hash, None);
+    }
+
+    #[test]
+    fn parses_every_chain_and_picks_the_default() {
+        let wallet = parse_wallet_show(MULTI).unwrap();
+
+        let ids: Vec<_> = wallet.chains.iter().map(|c| &c.chain_id[..8]).collect();
+        assert_eq!(ids, ["2d5e2c4e", "5b611b86", "e476187f"]);
+        assert_eq!(wallet.chain_id, wallet.chains[1].chain_id);
+        assert_eq!(wallet.default_chain.as_ref(), Some(&wallet.chain_id));
+        assert_eq!(wallet.chains.iter().filter(|c| c.is_default).count(), 1);
+
+        let unowned = &wallet.chains[0];
+        assert_eq!(unowned.owner, None);
+        assert_eq!(unowned.block_height, 12);
+        assert_eq!(
+            unowned.latest_block_hash.as_deref(),
+            Some("9f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a0")
+        );
+
+        let pending = &wallet.chains[2];
+        assert_eq!(pending.epoch, None);
+        assert!(!pending.is_default);
+        assert!(pending.owner.as_deref().unwrap().starts_with("0x7c0ffee"));
+    }
+
+    #[test]
+    fn falls_back_to_first_chain_without_default_tag() {
+        let output = MULTI.replace("Tags:                 DEFAULT\n", "");
+        let wallet = parse_wallet_show(&output).unwrap();
+
+        assert!(wallet.chains.iter().all(|c| !c.is_default));
+        assert_eq!(wallet.default_chain, None);
+        assert_eq!(wallet.chain_id, wallet.chains[0].chain_id);
+        assert_eq!(wallet.public_key, NO_OWNER);
+    }
+
+    #[test]
+    fn ignores_hex_looking_log_lines() {
+        // The old scraper returned the first 64-char hex line it found
+        let output = format!(
+            "{}\n{}",
+            "9f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a0", SINGLE
+        );
+        let wallet = parse_wallet_show(&output).unwrap();
+
+        assert_eq!(wallet.chains.len(), 1);
+        assert!(wallet.chain_id.starts_with("5b611b86"));
+    }
+
+    #[test]
+    fn strips_color_codes() {
+        let output = SINGLE.replace("Chain ID:", "\u{1b}[1mChain ID:\u{1b}[0m");
+        let wallet = parse_wallet_show(&output).unwrap();
+
+        assert!(wallet.chain_id.starts_with("5b611b86"));
+    }
+
+    #[test]
+    fn rejects_empty_wallet() {
+        let err = parse_wallet_show(EMPTY).unwrap_err();
+        assert!(err.to_string().contains("no chains"));
+    }
+
+    #[test]
+    fn parses_opened_chain_with_and_without_message_id() {
+        let chain_id = "e476187f6ddfeb9d588c7b45d3df334d5501d6499b3f9ad5595cae86cce16a65";
+        let message_id = "5b611b86cc1f54f73a4abfb4a2167c7327cc85a74cb2a5502431f67b554850b4:3:0";
+
+        let opened = parse_open_chain(&format!("{}\n{}\n", message_id, chain_id)).unwrap();
+        assert_eq!(opened.chain_id, chain_id);
+        assert_eq!(opened.message_id.as_deref(), Some(message_id));
+
+        let opened = parse_open_chain(&format!("{}\n", chain_id)).unwrap();
+        assert_eq!(opened.chain_id, chain_id);
+        assert_eq!(opened.message_id, None);
+
+        assert!(parse_open_chain("Error: insufficient balance\n").is_err());
+    }
+
+    #[test]
+    fn parses_requested_chain_before_its_owner() {
+        let chain_id = "e476187f6ddfeb9d588c7b45d3df334d5501d6499b3f9ad5595cae86cce16a65";
+        let owner = "0x7c0ffee1b0a8ad2b97dbf3e5e8d4e9e4d2c0a0e81c7f5b79c87b63b1d1c6f6a5";
+
+        let requested = parse_requested_chain(&format!("{}\n{}\n", chain_id, owner)).unwrap();
+        assert_eq!(requested, chain_id);
+
+        assert!(parse_requested_chain(&format!("{}\n", owner)).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        let bad_id = SINGLE.replace("5b611b86cc1f", "not-a-chain-");
+        assert!(parse_wallet_show(&bad_id).is_err());
+
+        let bad_height = SINGLE.replace("Blocks:               0", "Blocks:               many");
+        assert!(parse_wallet_show(&bad_height).is_err());
+    }
+}